@@ -0,0 +1,160 @@
+use std::error::Error;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::Duration;
+
+use common::Timings;
+use serde::{Deserialize, Serialize};
+
+/// Changes smaller than this are timer noise, however large they are relative to the old time.
+const NOISE_FLOOR: Duration = Duration::from_micros(50);
+
+/// The timings of one day in one benchmark run, in nanoseconds.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DayResult {
+    pub day: u8,
+    pub parse_ns: u64,
+    pub part1_ns: u64,
+    pub part2_ns: u64,
+}
+
+impl DayResult {
+    pub fn new(day: u8, timings: Timings) -> DayResult {
+        DayResult {
+            day,
+            parse_ns: timings.parse.as_nanos() as u64,
+            part1_ns: timings.part1.as_nanos() as u64,
+            part2_ns: timings.part2.as_nanos() as u64,
+        }
+    }
+
+    pub fn stages(&self) -> [(&'static str, Duration); 3] {
+        [
+            ("parse", Duration::from_nanos(self.parse_ns)),
+            ("part1", Duration::from_nanos(self.part1_ns)),
+            ("part2", Duration::from_nanos(self.part2_ns)),
+        ]
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Run {
+    /// Seconds since the Unix epoch.
+    pub timestamp: u64,
+    /// Whether the run used the worked examples rather than the real inputs.
+    pub example: bool,
+    pub iterations: u32,
+    pub results: Vec<DayResult>,
+}
+
+impl Run {
+    pub fn get(&self, day: u8) -> Option<&DayResult> {
+        self.results.iter().find(|result| result.day == day)
+    }
+}
+
+/// Every benchmark run so far, oldest first.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct History {
+    pub runs: Vec<Run>,
+}
+
+impl History {
+    /// Loads the history at `path`; a missing file is an empty history.
+    pub fn load(path: &Path) -> Result<History, Box<dyn Error>> {
+        match fs::read_to_string(path) {
+            Ok(contents) => serde_json::from_str(&contents)
+                .map_err(|err| format!("{}: {}", path.display(), err).into()),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(History::default()),
+            Err(err) => Err(format!("{}: {}", path.display(), err).into()),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), Box<dyn Error>> {
+        let contents = serde_json::to_string_pretty(self)?;
+        fs::write(path, contents + "\n")
+            .map_err(|err| format!("{}: {}", path.display(), err).into())
+    }
+
+    /// The most recent result for `day` measured on the same kind of input.
+    pub fn previous(&self, day: u8, example: bool) -> Option<&DayResult> {
+        self.runs
+            .iter()
+            .rev()
+            .filter(|run| run.example == example)
+            .find_map(|run| run.get(day))
+    }
+}
+
+/// Whether going from `previous` to `current` is slower by more than `threshold` (a fraction, so
+/// 0.2 is 20%) and by more than the noise floor.
+pub fn is_regression(previous: Duration, current: Duration, threshold: f64) -> bool {
+    current > previous + NOISE_FLOOR
+        && current.as_secs_f64() > previous.as_secs_f64() * (1.0 + threshold)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(day: u8, part2_ns: u64) -> DayResult {
+        DayResult {
+            day,
+            parse_ns: 1_000,
+            part1_ns: 2_000,
+            part2_ns,
+        }
+    }
+
+    #[test]
+    fn round_trips_and_finds_previous_result() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("history.json");
+        assert!(History::load(&path).unwrap().runs.is_empty());
+
+        let history = History {
+            runs: vec![
+                Run {
+                    timestamp: 1,
+                    example: false,
+                    iterations: 5,
+                    results: vec![result(1, 10), result(9, 20)],
+                },
+                Run {
+                    timestamp: 2,
+                    example: true,
+                    iterations: 5,
+                    results: vec![result(9, 30)],
+                },
+                Run {
+                    timestamp: 3,
+                    example: false,
+                    iterations: 5,
+                    results: vec![result(1, 40)],
+                },
+            ],
+        };
+        history.save(&path).unwrap();
+
+        let loaded = History::load(&path).unwrap();
+        assert_eq!(loaded.runs, history.runs);
+        assert_eq!(loaded.previous(1, false), Some(&result(1, 40)));
+        assert_eq!(loaded.previous(9, false), Some(&result(9, 20)));
+        assert_eq!(loaded.previous(9, true), Some(&result(9, 30)));
+        assert_eq!(loaded.previous(12, false), None);
+    }
+
+    #[test]
+    fn regressions_need_relative_and_absolute_slowdown() {
+        let ms = Duration::from_millis;
+        assert!(is_regression(ms(10), ms(13), 0.2));
+        assert!(!is_regression(ms(10), ms(11), 0.2));
+        assert!(!is_regression(ms(13), ms(10), 0.2));
+        assert!(!is_regression(
+            Duration::from_micros(1),
+            Duration::from_micros(40),
+            0.2
+        ));
+    }
+}
@@ -0,0 +1,610 @@
+use std::env;
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use bench::{DayResult, History};
+use clap::{Parser, Subcommand};
+use common::{diagnostic, Answer, Part, Puzzle, Timings, Unsolved};
+use fetch::Fetcher;
+use input::Source;
+use record::{Format, Record};
+use registry::Registry;
+
+mod bench;
+mod puzzles;
+mod record;
+mod registry;
+mod scaffold;
+
+const YEAR: u16 = 2020;
+/// Downloaded inputs live here, relative to the directory `aoc` is run from.
+const CACHE_DIR: &str = "inputs";
+const REGISTRY: &str = "answers.txt";
+/// Known-correct answers for the worked examples bundled with each day.
+const EXAMPLE_REGISTRY: &str = "answers-example.txt";
+const BENCH_HISTORY: &str = "bench-history.json";
+
+#[derive(Parser)]
+#[command(name = "aoc", about = "Advent of Code 2020 solutions")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Solve one day, or every day with --all
+    Run {
+        #[arg(long, required_unless_present = "all")]
+        day: Option<u8>,
+        /// Only solve this part (1 or 2)
+        #[arg(long, value_parser = clap::value_parser!(u8).range(1..=2))]
+        part: Option<u8>,
+        /// Input file (`-` for stdin); defaults to $AOC_INPUT, then the cached download, then
+        /// dayN/input.txt
+        #[arg(long, conflicts_with_all = ["all", "example"])]
+        input: Option<PathBuf>,
+        /// Use the worked example from the puzzle text instead of the real input
+        #[arg(long)]
+        example: bool,
+        #[arg(long, conflicts_with = "day")]
+        all: bool,
+        #[arg(long, value_enum, default_value_t = Format::Text)]
+        format: Format,
+    },
+    /// Download puzzle inputs into the local cache using the session cookie in $AOC_SESSION
+    Fetch {
+        #[arg(long, required_unless_present = "all")]
+        day: Option<u8>,
+        /// Fetch the input of every solved day
+        #[arg(long, conflicts_with = "day")]
+        all: bool,
+        #[arg(long, default_value_t = YEAR)]
+        year: u16,
+    },
+    /// Check every solution against the registry of known-correct answers in answers.txt
+    Verify {
+        /// Add answers for parts the registry does not know yet
+        #[arg(long)]
+        record: bool,
+        /// Check the worked examples against answers-example.txt instead of the real inputs
+        #[arg(long)]
+        example: bool,
+    },
+    /// Create the crate for a new day from a template and register it with the workspace and aoc
+    New {
+        #[arg(value_parser = clap::value_parser!(u8).range(1..=25))]
+        day: u8,
+        /// Also download the day's input into the cache
+        #[arg(long)]
+        fetch: bool,
+    },
+    /// Time parsing and each part separately and compare with the previous run
+    Bench {
+        /// Only benchmark this day
+        #[arg(long)]
+        day: Option<u8>,
+        /// Runs per day; the fastest time of each stage is kept
+        #[arg(long, default_value_t = 5, value_parser = clap::value_parser!(u32).range(1..))]
+        iterations: u32,
+        /// Use the worked examples instead of the real inputs
+        #[arg(long)]
+        example: bool,
+        /// Percentage slowdown over the previous run reported as a regression
+        #[arg(long, default_value_t = 20.0)]
+        threshold: f64,
+        /// JSON file the results are appended to
+        #[arg(long, default_value = BENCH_HISTORY)]
+        history: PathBuf,
+    },
+}
+
+fn main() {
+    match run(Cli::parse()) {
+        Ok(true) => {}
+        Ok(false) => process::exit(1),
+        Err(err) => {
+            eprintln!("error: {}", err);
+            process::exit(1);
+        }
+    }
+}
+
+/// Runs the chosen command, returning whether every puzzle it ran succeeded.
+fn run(cli: Cli) -> Result<bool, Box<dyn Error>> {
+    match cli.command {
+        Command::Run {
+            day,
+            part,
+            input,
+            example,
+            all,
+            format,
+        } => {
+            let parts = match part {
+                Some(1) => vec![Part::One],
+                Some(_) => vec![Part::Two],
+                None => Part::BOTH.to_vec(),
+            };
+
+            if all {
+                Ok(run_all(&parts, example, format))
+            } else {
+                let day = day.unwrap();
+                let puzzle = puzzles::find(day).ok_or(format!("day {} is not solved", day))?;
+                run_one(puzzle, &parts, input, example, format)
+            }
+        }
+        Command::Fetch { day, all, year } => {
+            let fetcher = fetcher()?;
+
+            let days: Vec<u8> = if all {
+                puzzles::all().iter().map(|puzzle| puzzle.day()).collect()
+            } else {
+                vec![day.unwrap()]
+            };
+            for day in days {
+                println!("day {}: {}", day, fetcher.fetch(year, day)?.display());
+            }
+            Ok(true)
+        }
+        Command::Verify { record, example } => {
+            let registry = if example { EXAMPLE_REGISTRY } else { REGISTRY };
+            verify(Path::new(registry), &puzzles::all(), example, record)
+        }
+        Command::New { day, fetch } => {
+            let dir = scaffold::new_day(Path::new("."), day)?;
+            println!("created {}", dir.display());
+            if fetch {
+                println!("input: {}", fetcher()?.fetch(YEAR, day)?.display());
+            }
+            Ok(true)
+        }
+        Command::Bench {
+            day,
+            iterations,
+            example,
+            threshold,
+            history,
+        } => {
+            let puzzles = match day {
+                Some(day) => vec![puzzles::find(day).ok_or(format!("day {} is not solved", day))?],
+                None => puzzles::all(),
+            };
+            bench(&puzzles, iterations, example, threshold / 100.0, &history)
+        }
+    }
+}
+
+fn fetcher() -> Result<Fetcher, String> {
+    let session = env::var(fetch::SESSION_VAR).map_err(|_| {
+        format!(
+            "set ${} to your adventofcode.com session cookie",
+            fetch::SESSION_VAR
+        )
+    })?;
+    Ok(Fetcher::new(&session, CACHE_DIR))
+}
+
+/// The cached download if there is one, otherwise the input kept next to the day's crate.
+fn default_input(day: u8) -> PathBuf {
+    let cached = fetch::cached_path(CACHE_DIR.as_ref(), YEAR, day);
+    if cached.exists() {
+        cached
+    } else {
+        PathBuf::from(format!("day{}", day)).join("input.txt")
+    }
+}
+
+fn timed(
+    puzzle: &dyn Puzzle,
+    input: &str,
+    part: Part,
+) -> (Result<Answer, Box<dyn Error>>, Duration) {
+    let start = Instant::now();
+    let answer = puzzle.solve(input, part);
+    (answer, start.elapsed())
+}
+
+fn run_one(
+    puzzle: &dyn Puzzle,
+    parts: &[Part],
+    input: Option<PathBuf>,
+    example: bool,
+    format: Format,
+) -> Result<bool, Box<dyn Error>> {
+    let example = Some(puzzle.example()).filter(|_| example);
+    let source = Source::resolve(input, example, default_input(puzzle.day()));
+    let input = source.read()?;
+    let mut ok = true;
+
+    for part in parts {
+        match (timed(puzzle, &input, *part), format) {
+            ((Ok(answer), elapsed), Format::Text) => {
+                println!("Part{}: {} ({:.2?})", part, answer, elapsed);
+                for (name, value) in &answer.details {
+                    println!("  {}: {}", name, value);
+                }
+            }
+            ((Ok(answer), elapsed), Format::Json) => {
+                println!(
+                    "{}",
+                    Record::solved(puzzle.day(), *part, &answer, elapsed).to_json()
+                );
+            }
+            ((Err(err), _), Format::Text) if err.is::<Unsolved>() => {
+                println!("Part{}: not solved yet", part);
+            }
+            ((Err(err), _), Format::Json) if err.is::<Unsolved>() => {
+                println!("{}", Record::unsolved(puzzle.day(), *part).to_json());
+            }
+            ((Err(err), _), Format::Text) => {
+                eprintln!("Part{}: error: {}", part, diagnostic(&source, err.as_ref()));
+                ok = false;
+            }
+            ((Err(err), _), Format::Json) => {
+                let error = diagnostic(&source, err.as_ref());
+                println!(
+                    "{}",
+                    Record::failed(puzzle.day(), Some(*part), error).to_json()
+                );
+                ok = false;
+            }
+        }
+    }
+
+    Ok(ok)
+}
+
+/// Solves every day against its own input file; `$AOC_INPUT` is ignored since it names one file.
+fn run_all(parts: &[Part], example: bool, format: Format) -> bool {
+    let mut ok = true;
+
+    if format == Format::Text {
+        println!(
+            "{:>3}  {:>4}  {:<20}  {:>12}",
+            "day", "part", "answer", "time"
+        );
+    }
+    for puzzle in puzzles::all() {
+        let source = if example {
+            Source::Example(puzzle.example())
+        } else {
+            Source::File(default_input(puzzle.day()))
+        };
+        let input = match source.read() {
+            Ok(input) => input,
+            Err(err) => {
+                match format {
+                    Format::Text => println!("{:>3}  {:>4}  error: {}", puzzle.day(), "-", err),
+                    Format::Json => println!(
+                        "{}",
+                        Record::failed(puzzle.day(), None, err.to_string()).to_json()
+                    ),
+                }
+                ok = false;
+                continue;
+            }
+        };
+
+        for part in parts {
+            let (answer, elapsed) = timed(puzzle, &input, *part);
+            if format == Format::Json {
+                let record = match &answer {
+                    Ok(answer) => Record::solved(puzzle.day(), *part, answer, elapsed),
+                    Err(err) if err.is::<Unsolved>() => Record::unsolved(puzzle.day(), *part),
+                    Err(err) => {
+                        ok = false;
+                        Record::failed(puzzle.day(), Some(*part), diagnostic(&source, err.as_ref()))
+                    }
+                };
+                println!("{}", record.to_json());
+                continue;
+            }
+
+            let answer = answer
+                .map(|answer| answer.to_string())
+                .unwrap_or_else(|err| {
+                    if err.is::<Unsolved>() {
+                        return "not solved yet".to_string();
+                    }
+                    ok = false;
+                    format!("error: {}", diagnostic(&source, err.as_ref()))
+                });
+            println!(
+                "{:>3}  {:>4}  {:<20}  {:>12.2?}",
+                puzzle.day(),
+                part,
+                answer,
+                elapsed
+            );
+        }
+    }
+
+    ok
+}
+
+/// Runs every puzzle against its cached input, or its worked example, and compares with the
+/// registry at `registry_path`. Only mismatches and errors count as failures; answers the registry
+/// does not know yet and parts that are not solved yet are reported as unknown.
+fn verify(
+    registry_path: &Path,
+    puzzles: &[&dyn Puzzle],
+    example: bool,
+    record: bool,
+) -> Result<bool, Box<dyn Error>> {
+    let mut registry = Registry::load(registry_path)?;
+    let (mut passed, mut failed, mut unknown) = (0, 0, 0);
+    let mut recorded = false;
+
+    for puzzle in puzzles {
+        let path = default_input(puzzle.day());
+        let source = if example {
+            Source::Example(puzzle.example())
+        } else if path.exists() {
+            Source::File(path)
+        } else {
+            println!(
+                "{:>3}  {:>4}  {:<8}  no input at {}",
+                puzzle.day(),
+                "-",
+                "skip",
+                path.display()
+            );
+            continue;
+        };
+        let input = source.read()?;
+
+        for part in Part::BOTH.iter() {
+            let (status, detail) = match (
+                puzzle.solve(&input, *part),
+                registry.get(puzzle.day(), *part),
+            ) {
+                (Err(err), _) if err.is::<Unsolved>() => {
+                    unknown += 1;
+                    ("unsolved", "not solved yet".to_string())
+                }
+                (Err(err), _) => {
+                    failed += 1;
+                    (
+                        "fail",
+                        format!("error: {}", diagnostic(&source, err.as_ref())),
+                    )
+                }
+                (Ok(answer), Some(known)) if answer.value == known => {
+                    passed += 1;
+                    ("pass", answer.value)
+                }
+                (Ok(answer), Some(known)) => {
+                    failed += 1;
+                    ("fail", format!("expected {}, got {}", known, answer))
+                }
+                (Ok(answer), None) => {
+                    unknown += 1;
+                    if record {
+                        registry.insert(puzzle.day(), *part, &answer.value);
+                        recorded = true;
+                    }
+                    ("unknown", answer.value)
+                }
+            };
+            println!(
+                "{:>3}  {:>4}  {:<8}  {}",
+                puzzle.day(),
+                part,
+                status,
+                detail
+            );
+        }
+    }
+
+    if recorded {
+        registry.save(registry_path)?;
+    }
+
+    println!("{} passed, {} failed, {} unknown", passed, failed, unknown);
+    Ok(failed == 0)
+}
+
+/// Times every stage of each puzzle, reports how it compares with the latest earlier result for
+/// the same day and appends the run to the history file.
+fn bench(
+    puzzles: &[&dyn Puzzle],
+    iterations: u32,
+    example: bool,
+    threshold: f64,
+    history_path: &Path,
+) -> Result<bool, Box<dyn Error>> {
+    let mut history = History::load(history_path)?;
+    let mut results = Vec::new();
+    let mut ok = true;
+    let mut regressions = 0;
+
+    println!(
+        "{:>3}  {:<5}  {:>12}  {:>12}  {:>8}",
+        "day", "stage", "time", "previous", "change"
+    );
+    for puzzle in puzzles {
+        let source = if example {
+            Source::Example(puzzle.example())
+        } else {
+            let path = default_input(puzzle.day());
+            if !path.exists() {
+                println!(
+                    "{:>3}  {:<5}  no input at {}",
+                    puzzle.day(),
+                    "-",
+                    path.display()
+                );
+                continue;
+            }
+            Source::File(path)
+        };
+        let input = source.read()?;
+
+        let mut fastest: Option<Timings> = None;
+        for _ in 0..iterations {
+            match puzzle.time(&input) {
+                Ok(timings) => {
+                    fastest = Some(fastest.map_or(timings, |fastest| fastest.min(timings)))
+                }
+                Err(err) if err.is::<Unsolved>() => {
+                    println!("{:>3}  {:<5}  not solved yet", puzzle.day(), "-");
+                    fastest = None;
+                    break;
+                }
+                Err(err) => {
+                    println!(
+                        "{:>3}  {:<5}  error: {}",
+                        puzzle.day(),
+                        "-",
+                        diagnostic(&source, err.as_ref())
+                    );
+                    ok = false;
+                    fastest = None;
+                    break;
+                }
+            }
+        }
+        let result = match fastest {
+            Some(timings) => DayResult::new(puzzle.day(), timings),
+            None => continue,
+        };
+
+        let previous = history.previous(puzzle.day(), example);
+        for (index, (stage, time)) in result.stages().iter().enumerate() {
+            let previous = previous.map(|previous| previous.stages()[index].1);
+            let (previous_text, change) = match previous {
+                Some(previous) => {
+                    let change = (time.as_secs_f64() / previous.as_secs_f64() - 1.0) * 100.0;
+                    let flag = if bench::is_regression(previous, *time, threshold) {
+                        regressions += 1;
+                        "  regression"
+                    } else {
+                        ""
+                    };
+                    (
+                        format!("{:.2?}", previous),
+                        format!("{:+.0}%{}", change, flag),
+                    )
+                }
+                None => ("-".to_string(), "-".to_string()),
+            };
+            println!(
+                "{:>3}  {:<5}  {:>12.2?}  {:>12}  {:>8}",
+                puzzle.day(),
+                stage,
+                time,
+                previous_text,
+                change
+            );
+        }
+        results.push(result);
+    }
+
+    if !results.is_empty() {
+        history.runs.push(bench::Run {
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|now| now.as_secs())
+                .unwrap_or_default(),
+            example,
+            iterations,
+            results,
+        });
+        history.save(history_path)?;
+    }
+
+    println!("{} regression(s)", regressions);
+    Ok(ok)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn example_registry() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("..")
+            .join(EXAMPLE_REGISTRY)
+    }
+
+    #[test]
+    fn verifies_examples_against_the_registry() {
+        assert!(verify(&example_registry(), &puzzles::all(), true, false).unwrap());
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("answers.txt");
+        let mut registry = Registry::load(&example_registry()).unwrap();
+        registry.insert(1, Part::One, "1");
+        registry.save(&path).unwrap();
+        assert!(!verify(&path, &puzzles::all(), true, false).unwrap());
+    }
+
+    /// What `aoc new 10` writes, before anyone has solved it.
+    struct NewDay;
+
+    impl common::Solution for NewDay {
+        const DAY: u8 = 10;
+        const EXAMPLE: &'static str = "";
+        type Input = ();
+
+        fn parse(&self, _input: &str) -> Result<(), Box<dyn Error>> {
+            Ok(())
+        }
+
+        fn part1(&self, _input: &()) -> Result<Answer, Box<dyn Error>> {
+            Err(Unsolved(Part::One).into())
+        }
+
+        fn part2(&self, _input: &()) -> Result<Answer, Box<dyn Error>> {
+            Err(Unsolved(Part::Two).into())
+        }
+    }
+
+    #[test]
+    fn counts_new_days_as_unknown() {
+        let root = tempfile::tempdir().unwrap();
+        fs::write(
+            root.path().join("Cargo.toml"),
+            "[workspace]\nmembers = [\n    \"day9\",\n]\n",
+        )
+        .unwrap();
+        fs::create_dir_all(root.path().join("aoc/src")).unwrap();
+        fs::write(
+            root.path().join("aoc/Cargo.toml"),
+            "[dependencies]\nday9 = { path = \"../day9\" }\n",
+        )
+        .unwrap();
+        fs::write(
+            root.path().join("aoc/src/puzzles.rs"),
+            "    vec![\n        &day9::Day9,\n    ]\n",
+        )
+        .unwrap();
+        let dir = scaffold::new_day(root.path(), 10).unwrap();
+        let stub = fs::read_to_string(dir.join("src/lib.rs")).unwrap();
+        assert!(stub.contains("Err(Unsolved(Part::One).into())"));
+        assert!(stub.contains("Err(Unsolved(Part::Two).into())"));
+
+        let mut puzzles = puzzles::all();
+        puzzles.push(&NewDay);
+        assert!(verify(&example_registry(), &puzzles, true, false).unwrap());
+    }
+
+    #[test]
+    fn records_unknown_answers() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("answers.txt");
+        assert!(verify(&path, &puzzles::all(), true, true).unwrap());
+
+        let recorded = Registry::load(&path).unwrap();
+        assert_eq!(recorded.get(1, Part::Two), Some("241861950"));
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            fs::read_to_string(example_registry()).unwrap()
+        );
+    }
+}
@@ -0,0 +1,22 @@
+use common::Puzzle;
+
+/// Every solved day, in order. New days need to be added here to be picked up by the runner.
+pub fn all() -> Vec<&'static dyn Puzzle> {
+    vec![
+        &day1::Day1,
+        &day2::Day2,
+        &day3::Day3,
+        &day4::Day4,
+        &day5::Day5,
+        &day6::Day6,
+        &day7::Day7,
+        &day8::Day8,
+        &day9::Day9,
+        &day11::Day11,
+        &day12::Day12,
+    ]
+}
+
+pub fn find(day: u8) -> Option<&'static dyn Puzzle> {
+    all().into_iter().find(|puzzle| puzzle.day() == day)
+}
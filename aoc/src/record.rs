@@ -0,0 +1,105 @@
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use common::{Answer, Part};
+use serde::Serialize;
+
+/// How `aoc run` prints its results.
+#[derive(Debug, Clone, Copy, PartialEq, clap::ValueEnum)]
+pub enum Format {
+    /// Human-readable lines and tables
+    Text,
+    /// One JSON object per line and part
+    Json,
+}
+
+/// The outcome of solving one part, as printed by `aoc run --format json`.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct Record {
+    pub day: u8,
+    /// Missing when the input could not be read, so neither part was attempted.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub part: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub answer: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub elapsed_ns: Option<u64>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub details: BTreeMap<String, String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Set when the part has not been solved yet, which is not an error.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub unsolved: bool,
+}
+
+impl Record {
+    pub fn solved(day: u8, part: Part, answer: &Answer, elapsed: Duration) -> Record {
+        Record {
+            day,
+            part: Some(part_number(part)),
+            answer: Some(answer.value.clone()),
+            elapsed_ns: Some(elapsed.as_nanos() as u64),
+            details: answer.details.iter().cloned().collect(),
+            error: None,
+            unsolved: false,
+        }
+    }
+
+    pub fn failed(day: u8, part: Option<Part>, error: String) -> Record {
+        Record {
+            day,
+            part: part.map(part_number),
+            answer: None,
+            elapsed_ns: None,
+            details: BTreeMap::new(),
+            error: Some(error),
+            unsolved: false,
+        }
+    }
+
+    pub fn unsolved(day: u8, part: Part) -> Record {
+        Record {
+            day,
+            part: Some(part_number(part)),
+            answer: None,
+            elapsed_ns: None,
+            details: BTreeMap::new(),
+            error: None,
+            unsolved: true,
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("records only hold strings and numbers")
+    }
+}
+
+fn part_number(part: Part) -> u8 {
+    match part {
+        Part::One => 1,
+        Part::Two => 2,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serialises_answers_and_errors() {
+        let answer = Answer::new(514579).with_detail("entries", "1721 299");
+        assert_eq!(
+            Record::solved(1, Part::One, &answer, Duration::from_nanos(1500)).to_json(),
+            r#"{"day":1,"part":1,"answer":"514579","elapsed_ns":1500,"details":{"entries":"1721 299"}}"#
+        );
+        assert_eq!(
+            Record::failed(12, None, "no input".to_string()).to_json(),
+            r#"{"day":12,"error":"no input"}"#
+        );
+        assert_eq!(
+            Record::unsolved(10, Part::Two).to_json(),
+            r#"{"day":10,"part":2,"unsolved":true}"#
+        );
+    }
+}
@@ -0,0 +1,128 @@
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use common::Part;
+
+const HEADER: &str = "# Known-correct answers, one per line: <day> <part> <answer>\n";
+
+/// Answers that have been confirmed correct, keyed by day and part.
+#[derive(Default)]
+pub struct Registry {
+    answers: BTreeMap<(u8, Part), String>,
+}
+
+impl Registry {
+    /// Loads the registry at `path`; a missing file is an empty registry.
+    pub fn load(path: &Path) -> Result<Registry, Box<dyn Error>> {
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Registry::default()),
+            Err(err) => return Err(format!("{}: {}", path.display(), err).into()),
+        };
+
+        let mut answers = BTreeMap::new();
+        for (index, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let mut fields = line.splitn(3, char::is_whitespace);
+            let day = fields.next().and_then(|day| day.parse().ok());
+            let part = match fields.next() {
+                Some("1") => Some(Part::One),
+                Some("2") => Some(Part::Two),
+                _ => None,
+            };
+            let answer = fields
+                .next()
+                .map(str::trim)
+                .filter(|answer| !answer.is_empty());
+
+            match (day, part, answer) {
+                (Some(day), Some(part), Some(answer)) => {
+                    answers.insert((day, part), answer.to_string());
+                }
+                _ => {
+                    return Err(format!(
+                        "{}:{}: expected \"<day> <part> <answer>\"",
+                        path.display(),
+                        index + 1
+                    )
+                    .into())
+                }
+            }
+        }
+
+        Ok(Registry { answers })
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), Box<dyn Error>> {
+        let mut contents = HEADER.to_string();
+        for ((day, part), answer) in &self.answers {
+            contents.push_str(&format!("{} {} {}\n", day, part, answer));
+        }
+
+        fs::write(path, contents).map_err(|err| format!("{}: {}", path.display(), err).into())
+    }
+
+    pub fn get(&self, day: u8, part: Part) -> Option<&str> {
+        self.answers.get(&(day, part)).map(String::as_str)
+    }
+
+    pub fn insert(&mut self, day: u8, part: Part, answer: &str) {
+        self.answers.insert((day, part), answer.to_string());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn saves_and_loads_answers() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("answers.txt");
+        assert!(Registry::load(&path).unwrap().get(1, Part::One).is_none());
+
+        let mut registry = Registry::default();
+        registry.insert(12, Part::Two, "286");
+        registry.insert(1, Part::One, "514579");
+        registry.insert(4, Part::One, "two words");
+        registry.save(&path).unwrap();
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            format!("{}1 1 514579\n4 1 two words\n12 2 286\n", HEADER)
+        );
+
+        let loaded = Registry::load(&path).unwrap();
+        assert_eq!(loaded.get(1, Part::One), Some("514579"));
+        assert_eq!(loaded.get(4, Part::One), Some("two words"));
+        assert_eq!(loaded.get(12, Part::Two), Some("286"));
+        assert_eq!(loaded.get(12, Part::One), None);
+    }
+
+    #[test]
+    fn rejects_malformed_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("answers.txt");
+        let error = |contents: &str| {
+            fs::write(&path, contents).unwrap();
+            Registry::load(&path).err().unwrap().to_string()
+        };
+
+        let at_line_2 = format!("{}:2: expected \"<day> <part> <answer>\"", path.display());
+        assert_eq!(error("# comment\n1 3 42\n"), at_line_2);
+        assert_eq!(error("\nday 1 42\n"), at_line_2);
+        assert_eq!(error("1 1 5\n2 1\n"), at_line_2);
+
+        fs::write(&path, "  # indented comment\n\n 5 2 120 \n").unwrap();
+        assert_eq!(
+            Registry::load(&path).unwrap().get(5, Part::Two),
+            Some("120")
+        );
+    }
+}
@@ -0,0 +1,177 @@
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const TEMPLATES: [(&str, &str); 4] = [
+    ("Cargo.toml", include_str!("../templates/Cargo.toml.tmpl")),
+    ("src/lib.rs", include_str!("../templates/lib.rs.tmpl")),
+    ("src/main.rs", include_str!("../templates/main.rs.tmpl")),
+    (
+        "fixtures/example.expected",
+        include_str!("../templates/example.expected.tmpl"),
+    ),
+];
+
+/// Creates the `dayN` crate under the workspace at `root` and registers it with the workspace and
+/// the runner. Returns the crate directory.
+pub fn new_day(root: &Path, day: u8) -> Result<PathBuf, Box<dyn Error>> {
+    let name = format!("day{}", day);
+    let dir = root.join(&name);
+    if dir.exists() {
+        return Err(format!("{} already exists", dir.display()).into());
+    }
+
+    // Edit the shared files first, so a workspace that cannot be updated is left as it was.
+    let manifest = root.join("Cargo.toml");
+    let members = add_member(&read(&manifest)?, &name)?;
+    let runner_manifest = root.join("aoc").join("Cargo.toml");
+    let dependencies = add_line(
+        &read(&runner_manifest)?,
+        &format!("{} = {{ path = \"../{}\" }}", name, name),
+        |line| day_number(line.split_whitespace().next()?),
+    )?;
+    let puzzles_path = root.join("aoc").join("src").join("puzzles.rs");
+    let puzzles = add_line(
+        &read(&puzzles_path)?,
+        &format!("        &{}::Day{},", name, day),
+        |line| day_number(line.trim().strip_prefix('&')?.split("::").next()?),
+    )?;
+
+    for (file, template) in TEMPLATES.iter() {
+        write(
+            &dir.join(file),
+            &template.replace("{day}", &day.to_string()),
+        )?;
+    }
+    write(&dir.join("fixtures").join("example.txt"), "")?;
+
+    write(&manifest, &members)?;
+    write(&runner_manifest, &dependencies)?;
+    write(&puzzles_path, &puzzles)?;
+
+    Ok(dir)
+}
+
+fn read(path: &Path) -> Result<String, Box<dyn Error>> {
+    fs::read_to_string(path).map_err(|err| format!("{}: {}", path.display(), err).into())
+}
+
+fn write(path: &Path, contents: &str) -> Result<(), Box<dyn Error>> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|err| format!("{}: {}", dir.display(), err))?;
+    }
+    fs::write(path, contents).map_err(|err| format!("{}: {}", path.display(), err).into())
+}
+
+/// `12` for `day12`, and `None` for names of crates that are not days.
+fn day_number(name: &str) -> Option<u8> {
+    name.trim_matches(|c| c == '"' || c == ',')
+        .strip_prefix("day")?
+        .parse()
+        .ok()
+}
+
+/// Adds `name` to the workspace `members` list, keeping the days in numeric order.
+fn add_member(manifest: &str, name: &str) -> Result<String, Box<dyn Error>> {
+    let start = manifest
+        .find("members = [")
+        .ok_or("Cargo.toml has no workspace members list")?;
+    let end = start
+        + manifest[start..]
+            .find(']')
+            .ok_or("unterminated members list")?;
+
+    let mut members: Vec<&str> = manifest[start..end]
+        .lines()
+        .skip(1)
+        .map(|line| line.trim().trim_end_matches(',').trim_matches('"'))
+        .filter(|member| !member.is_empty())
+        .collect();
+    members.push(name);
+    members.sort_by_key(|member| match day_number(member) {
+        Some(day) => ("day".to_string(), day),
+        None => (member.to_string(), 0),
+    });
+
+    let mut list = String::from("members = [\n");
+    for member in members {
+        list.push_str(&format!("    \"{}\",\n", member));
+    }
+    Ok(format!(
+        "{}{}{}",
+        &manifest[..start],
+        list,
+        &manifest[end..]
+    ))
+}
+
+/// Inserts `new_line` among the lines that `day_of` recognises as belonging to a day, after the
+/// last one for an earlier day.
+fn add_line<F>(text: &str, new_line: &str, day_of: F) -> Result<String, Box<dyn Error>>
+where
+    F: Fn(&str) -> Option<u8>,
+{
+    let day = day_of(new_line).expect("new lines belong to a day");
+    let lines: Vec<&str> = text.lines().collect();
+    let days: Vec<(usize, u8)> = lines
+        .iter()
+        .enumerate()
+        .filter_map(|(index, line)| day_of(line).map(|day| (index, day)))
+        .collect();
+
+    let index = match days.iter().rev().find(|(_, other)| *other < day) {
+        Some((index, _)) => index + 1,
+        None => days.first().ok_or("found no existing day to add after")?.0,
+    };
+
+    let mut result: Vec<&str> = lines[..index].to_vec();
+    result.push(new_line);
+    result.extend(&lines[index..]);
+    Ok(result.join("\n") + "\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn creates_and_registers_a_day() {
+        let root = tempfile::tempdir().unwrap();
+        let root = root.path();
+        write(
+            &root.join("Cargo.toml"),
+            "[workspace]\nmembers = [\n    \"aoc\",\n    \"day1\",\n    \"day12\",\n    \"grid\",\n]\nresolver = \"2\"\n",
+        )
+        .unwrap();
+        write(
+            &root.join("aoc/Cargo.toml"),
+            "[dependencies]\ncommon = { workspace = true }\nday1 = { path = \"../day1\" }\nday12 = { path = \"../day12\" }\n",
+        )
+        .unwrap();
+        write(
+            &root.join("aoc/src/puzzles.rs"),
+            "    vec![\n        &day1::Day1,\n        &day12::Day12,\n    ]\n",
+        )
+        .unwrap();
+
+        let dir = new_day(root, 10).unwrap();
+        assert_eq!(
+            read(&root.join("Cargo.toml")).unwrap(),
+            "[workspace]\nmembers = [\n    \"aoc\",\n    \"day1\",\n    \"day10\",\n    \"day12\",\n    \"grid\",\n]\nresolver = \"2\"\n"
+        );
+        assert_eq!(
+            read(&root.join("aoc/Cargo.toml")).unwrap(),
+            "[dependencies]\ncommon = { workspace = true }\nday1 = { path = \"../day1\" }\nday10 = { path = \"../day10\" }\nday12 = { path = \"../day12\" }\n"
+        );
+        assert_eq!(
+            read(&root.join("aoc/src/puzzles.rs")).unwrap(),
+            "    vec![\n        &day1::Day1,\n        &day10::Day10,\n        &day12::Day12,\n    ]\n"
+        );
+        assert!(read(&dir.join("src/lib.rs"))
+            .unwrap()
+            .contains("impl Solution for Day10 {\n    const DAY: u8 = 10;"));
+        assert_eq!(read(&dir.join("fixtures/example.txt")).unwrap(), "");
+
+        assert!(new_day(root, 10).is_err());
+    }
+}
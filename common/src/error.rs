@@ -0,0 +1,120 @@
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::Part;
+
+/// Malformed puzzle input, with the 1-based line and column of the problem when they are known.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+    pub message: String,
+}
+
+impl ParseError {
+    pub fn new<S: Into<String>>(message: S) -> ParseError {
+        ParseError {
+            line: None,
+            column: None,
+            message: message.into(),
+        }
+    }
+
+    pub fn at_line(mut self, line: usize) -> ParseError {
+        self.line = Some(line);
+        self
+    }
+
+    pub fn at_column(mut self, column: usize) -> ParseError {
+        self.column = Some(column);
+        self
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.line, self.column) {
+            (Some(line), Some(column)) => write!(f, "{}:{}: {}", line, column, self.message),
+            (Some(line), None) => write!(f, "{}: {}", line, self.message),
+            _ => write!(f, "{}", self.message),
+        }
+    }
+}
+
+impl Error for ParseError {}
+
+impl From<grid::ParseError> for ParseError {
+    fn from(err: grid::ParseError) -> ParseError {
+        match err {
+            grid::ParseError::InvalidCell {
+                line,
+                column,
+                found,
+            } => ParseError::new(format!("unexpected {:?}", found))
+                .at_line(line)
+                .at_column(column),
+            grid::ParseError::Ragged {
+                line,
+                expected,
+                found,
+            } => ParseError::new(format!(
+                "row is {} cells wide, expected {}",
+                found, expected
+            ))
+            .at_line(line),
+        }
+    }
+}
+
+/// What a part returns until someone solves it, as the stubs `aoc new` writes do. Runners report
+/// it as unsolved instead of as a failure.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Unsolved(pub Part);
+
+impl fmt::Display for Unsolved {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "part {} is not solved yet", self.0)
+    }
+}
+
+impl Error for Unsolved {}
+
+/// Parses every line with `parse_line`, filling in the line number of the first error.
+pub fn parse_lines<T, F>(input: &str, mut parse_line: F) -> Result<Vec<T>, ParseError>
+where
+    F: FnMut(&str) -> Result<T, ParseError>,
+{
+    input
+        .lines()
+        .enumerate()
+        .map(|(index, line)| {
+            parse_line(line).map_err(|err| match err.line {
+                Some(_) => err,
+                None => err.at_line(index + 1),
+            })
+        })
+        .collect()
+}
+
+/// Parses `text`, found at the 1-based `column` of its line, as a number.
+pub fn parse_number<T: FromStr>(text: &str, column: usize) -> Result<T, ParseError> {
+    text.parse().map_err(|_| {
+        ParseError::new(format!("expected a number, found {:?}", text)).at_column(column)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_line_and_column() {
+        let err = parse_lines("1\n2\nx3", |line| parse_number::<i32>(line, 1)).unwrap_err();
+        assert_eq!(err.to_string(), "3:1: expected a number, found \"x3\"");
+        assert_eq!(
+            parse_lines("1\n-2", |line| parse_number::<i32>(line, 1)),
+            Ok(vec![1, -2])
+        );
+    }
+}
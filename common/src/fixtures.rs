@@ -0,0 +1,79 @@
+//! Runs the fixture tests that the `fixtures` build crate generates from a day's `fixtures`
+//! directory.
+
+/// Includes a test for every case in the crate's `fixtures` directory, solved with `$solution`.
+/// The crate's build script must be the `fixtures` crate's `generate.rs`.
+#[macro_export]
+macro_rules! fixture_tests {
+    ($solution:expr) => {
+        #[cfg(test)]
+        mod fixtures {
+            #[allow(unused_imports)]
+            use super::*;
+
+            /// Solves `input` and panics with a description of every expectation it does not
+            /// meet. An expectation is a part, the detail it names if any, and the value wanted.
+            fn check(name: &str, input: &str, expected: &[(u8, Option<&str>, &str)]) {
+                use $crate::Puzzle;
+
+                let puzzle = &$solution;
+                let input = $crate::normalise(input);
+                let mut failures = Vec::new();
+
+                for part in $crate::Part::BOTH.iter() {
+                    let number = match part {
+                        $crate::Part::One => 1,
+                        $crate::Part::Two => 2,
+                    };
+                    let wanted: Vec<_> = expected
+                        .iter()
+                        .filter(|(expected_part, _, _)| *expected_part == number)
+                        .collect();
+                    if wanted.is_empty() {
+                        continue;
+                    }
+
+                    let answer = match puzzle.solve(&input, *part) {
+                        Ok(answer) => answer,
+                        Err(err) => {
+                            failures.push(format!("part{}: error: {}", number, err));
+                            continue;
+                        }
+                    };
+
+                    for (_, detail, value) in wanted {
+                        let (key, actual) = match detail {
+                            None => (format!("part{}", number), Some(&answer.value)),
+                            Some(detail) => (
+                                format!("part{}.{}", number, detail),
+                                answer
+                                    .details
+                                    .iter()
+                                    .find(|(name, _)| name == detail)
+                                    .map(|(_, value)| value),
+                            ),
+                        };
+
+                        match actual {
+                            Some(actual) if actual == value => {}
+                            Some(actual) => failures
+                                .push(format!("{}: expected {}, got {}", key, value, actual)),
+                            None => failures.push(format!("{}: answer has no such detail", key)),
+                        }
+                    }
+                }
+
+                if !failures.is_empty() {
+                    panic!(
+                        "day {} fixture {:?} failed:\n  {}",
+                        puzzle.day(),
+                        name,
+                        failures.join("\n  ")
+                    );
+                }
+            }
+
+            include!(concat!(env!("OUT_DIR"), "/fixtures.rs"));
+        }
+    };
+}
@@ -0,0 +1,180 @@
+use std::env;
+use std::error::Error;
+use std::fmt;
+use std::path::PathBuf;
+use std::process;
+use std::time::{Duration, Instant};
+
+pub use input::normalise;
+use input::Source;
+
+pub use error::{parse_lines, parse_number, ParseError, Unsolved};
+
+mod error;
+mod fixtures;
+
+/// The answer to one part of a puzzle.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Answer {
+    pub value: String,
+    /// Named intermediate values that explain how the answer was reached, in the order added.
+    pub details: Vec<(String, String)>,
+}
+
+impl Answer {
+    pub fn new<T: fmt::Display>(value: T) -> Answer {
+        Answer {
+            value: value.to_string(),
+            details: Vec::new(),
+        }
+    }
+
+    pub fn with_detail<T: fmt::Display>(mut self, name: &str, value: T) -> Answer {
+        self.details.push((name.to_string(), value.to_string()));
+        self
+    }
+}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.value)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Part {
+    One,
+    Two,
+}
+
+impl Part {
+    pub const BOTH: [Part; 2] = [Part::One, Part::Two];
+}
+
+impl fmt::Display for Part {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(match self {
+            Part::One => "1",
+            Part::Two => "2",
+        })
+    }
+}
+
+/// How long each stage of solving a puzzle took.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Timings {
+    pub parse: Duration,
+    pub part1: Duration,
+    pub part2: Duration,
+}
+
+impl Timings {
+    /// The fastest time of each stage across both measurements.
+    pub fn min(self, other: Timings) -> Timings {
+        Timings {
+            parse: self.parse.min(other.parse),
+            part1: self.part1.min(other.part1),
+            part2: self.part2.min(other.part2),
+        }
+    }
+}
+
+/// A day's puzzle: how to read its input and how to answer both parts from it.
+pub trait Solution {
+    const DAY: u8;
+    /// The worked example from the puzzle text.
+    const EXAMPLE: &'static str;
+    type Input;
+
+    fn parse(&self, input: &str) -> Result<Self::Input, Box<dyn Error>>;
+    fn part1(&self, input: &Self::Input) -> Result<Answer, Box<dyn Error>>;
+    fn part2(&self, input: &Self::Input) -> Result<Answer, Box<dyn Error>>;
+}
+
+/// Object-safe view of a `Solution`, so solutions for different days can share a list.
+pub trait Puzzle {
+    fn day(&self) -> u8;
+    fn example(&self) -> &'static str;
+    fn solve(&self, input: &str, part: Part) -> Result<Answer, Box<dyn Error>>;
+    /// Parses `input` and solves both parts once, timing each stage separately.
+    fn time(&self, input: &str) -> Result<Timings, Box<dyn Error>>;
+}
+
+impl<S: Solution> Puzzle for S {
+    fn day(&self) -> u8 {
+        S::DAY
+    }
+
+    fn example(&self) -> &'static str {
+        S::EXAMPLE
+    }
+
+    fn solve(&self, input: &str, part: Part) -> Result<Answer, Box<dyn Error>> {
+        let parsed = self.parse(input)?;
+        match part {
+            Part::One => self.part1(&parsed),
+            Part::Two => self.part2(&parsed),
+        }
+    }
+
+    fn time(&self, input: &str) -> Result<Timings, Box<dyn Error>> {
+        let start = Instant::now();
+        let parsed = self.parse(input)?;
+        let parse = start.elapsed();
+
+        let start = Instant::now();
+        self.part1(&parsed)?;
+        let part1 = start.elapsed();
+
+        let start = Instant::now();
+        self.part2(&parsed)?;
+        let part2 = start.elapsed();
+
+        Ok(Timings {
+            parse,
+            part1,
+            part2,
+        })
+    }
+}
+
+/// Describes a failure to solve the input from `source`, pointing into the input if the failure
+/// was a `ParseError` that knows where the problem is.
+pub fn diagnostic(source: &Source, err: &(dyn Error + 'static)) -> String {
+    match err.downcast_ref::<ParseError>() {
+        Some(ParseError { line: Some(_), .. }) => format!("{}:{}", source, err),
+        Some(_) => format!("{}: {}", source, err),
+        None => err.to_string(),
+    }
+}
+
+/// Entry point shared by every day's binary. Reads the file named by the first argument (`-` for
+/// stdin), the bundled example with `--example`, or `input.txt` in the current directory, then
+/// solves both parts. Errors are reported on stderr and exit with status 1.
+pub fn run_main<S: Solution>(solution: &S) -> Result<(), Box<dyn Error>> {
+    let mut path = None;
+    let mut example = None;
+    for arg in env::args().skip(1) {
+        if arg == "--example" {
+            example = Some(S::EXAMPLE);
+        } else {
+            path = Some(PathBuf::from(arg));
+        }
+    }
+
+    let source = Source::resolve(path, example, PathBuf::from("input.txt"));
+    if let Err(err) = solve_main(solution, &source) {
+        eprintln!("error: {}", diagnostic(&source, err.as_ref()));
+        process::exit(1);
+    }
+    Ok(())
+}
+
+fn solve_main<S: Solution>(solution: &S, source: &Source) -> Result<(), Box<dyn Error>> {
+    let input = source.read()?;
+
+    let parsed = solution.parse(&input)?;
+    println!("Part1: {}", solution.part1(&parsed)?);
+    println!("Part2: {}", solution.part2(&parsed)?);
+    Ok(())
+}
@@ -0,0 +1,378 @@
+//! Solving expense reports too large to hold in memory. The report is read in chunks that are
+//! sorted and written out as runs, the runs are merged into one sorted file of fixed-size
+//! records, and the two- and three-pointer searches then walk that file through small caches.
+
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::env;
+use std::error::Error;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+use common::{parse_number, ParseError};
+use tempfile::TempDir;
+
+use crate::ksum::Combination;
+
+/// Entries sorted in memory at a time before being written out as a run.
+pub const DEFAULT_CHUNK: usize = 1 << 20;
+
+/// Records a cursor reads from disk at a time.
+const BLOCK: u64 = 4096;
+
+/// An entry's value followed by its 0-based line, both little-endian.
+const RECORD_BYTES: u64 = 16;
+
+type Record = (i64, u64);
+
+fn write_record(out: &mut impl Write, (value, line): Record) -> io::Result<()> {
+    out.write_all(&value.to_le_bytes())?;
+    out.write_all(&line.to_le_bytes())
+}
+
+/// The next record of `input`, or `None` at its end.
+fn read_record(input: &mut impl Read) -> io::Result<Option<Record>> {
+    let mut bytes = [0; RECORD_BYTES as usize];
+    match input.read_exact(&mut bytes) {
+        Ok(()) => Ok(Some(decode(&bytes))),
+        Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+fn decode(bytes: &[u8]) -> Record {
+    let mut value = [0; 8];
+    let mut line = [0; 8];
+    value.copy_from_slice(&bytes[..8]);
+    line.copy_from_slice(&bytes[8..16]);
+    (i64::from_le_bytes(value), u64::from_le_bytes(line))
+}
+
+/// A report sorted by value on disk. The files live in a temporary directory, by default under the
+/// system's, that is removed when the report is dropped.
+pub struct SortedReport {
+    dir: TempDir,
+    len: u64,
+}
+
+impl SortedReport {
+    /// Sorts the report at `path`, holding at most `chunk` entries in memory at once.
+    pub fn from_file(path: &Path, chunk: usize) -> Result<SortedReport, Box<dyn Error>> {
+        let file = File::open(path).map_err(|err| format!("{}: {}", path.display(), err))?;
+        SortedReport::sort(BufReader::new(file), chunk)
+    }
+
+    /// Like `from_file`, but keeps the sorted runs in a temporary directory under `tmp_dir`.
+    pub fn from_file_in(
+        path: &Path,
+        chunk: usize,
+        tmp_dir: &Path,
+    ) -> Result<SortedReport, Box<dyn Error>> {
+        let file = File::open(path).map_err(|err| format!("{}: {}", path.display(), err))?;
+        SortedReport::sort_in(BufReader::new(file), chunk, tmp_dir)
+    }
+
+    /// Sorts a report with one entry per line, holding at most `chunk` entries in memory at once.
+    pub fn sort(input: impl BufRead, chunk: usize) -> Result<SortedReport, Box<dyn Error>> {
+        SortedReport::sort_in(input, chunk, &env::temp_dir())
+    }
+
+    /// Like `sort`, but keeps the sorted runs in a temporary directory under `tmp_dir`.
+    pub fn sort_in(
+        input: impl BufRead,
+        chunk: usize,
+        tmp_dir: &Path,
+    ) -> Result<SortedReport, Box<dyn Error>> {
+        let dir = tempfile::tempdir_in(tmp_dir)
+            .map_err(|err| format!("{}: {}", tmp_dir.display(), err))?;
+        let chunk = chunk.max(1);
+        let mut runs = Vec::new();
+        let mut entries = Vec::with_capacity(chunk);
+        let mut len = 0;
+
+        for (index, line) in input.lines().enumerate() {
+            let line = line?;
+            let value: i64 = parse_number(line.trim_end(), 1)
+                .map_err(|err: ParseError| err.at_line(index + 1))?;
+            entries.push((value, index as u64));
+            len += 1;
+            if entries.len() == chunk {
+                runs.push(write_run(&dir, runs.len(), &mut entries)?);
+            }
+        }
+        if !entries.is_empty() {
+            runs.push(write_run(&dir, runs.len(), &mut entries)?);
+        }
+
+        merge(&dir, runs)?;
+        Ok(SortedReport { dir, len })
+    }
+
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    fn cursor(&self) -> io::Result<Cursor> {
+        Ok(Cursor {
+            file: File::open(self.dir.path().join("sorted"))?,
+            start: 0,
+            block: Vec::new(),
+        })
+    }
+
+    /// The first `k` entries, for `k` of 2 or 3, that sum to `target`.
+    pub fn find(&self, k: usize, target: i64) -> Result<Option<Combination<i64>>, Box<dyn Error>> {
+        let mut low = self.cursor()?;
+        let mut high = self.cursor()?;
+        let found = match k {
+            2 => self.pair(&mut low, &mut high, 0, target)?,
+            3 => {
+                let mut outer = self.cursor()?;
+                let mut found = None;
+                for first in 0..self.len.saturating_sub(2) {
+                    let entry = outer.get(first)?;
+                    // Everything after this entry is at least as large, so the sum only grows.
+                    match entry.0.checked_mul(3) {
+                        Some(sum) if sum > target => break,
+                        None if entry.0 > 0 => break,
+                        _ => {}
+                    }
+
+                    // Only a negative entry can push the rest past `i64::MAX`, which no pair of
+                    // entries reaches.
+                    let rest = match target.checked_sub(entry.0) {
+                        Some(rest) => rest,
+                        None => continue,
+                    };
+                    if let Some(mut pair) = self.pair(&mut low, &mut high, first + 1, rest)? {
+                        pair.push(entry);
+                        found = Some(pair);
+                        break;
+                    }
+                }
+                found
+            }
+            _ => return Err(format!("can only search for 2 or 3 entries, not {}", k).into()),
+        };
+
+        Ok(found.map(|mut entries| {
+            entries.sort_unstable_by_key(|(_, line)| *line);
+            Combination {
+                indices: entries.iter().map(|(_, line)| *line as usize).collect(),
+                values: entries.iter().map(|(value, _)| *value).collect(),
+            }
+        }))
+    }
+
+    /// Two entries at or after `start` that sum to `target`.
+    fn pair(
+        &self,
+        low_cursor: &mut Cursor,
+        high_cursor: &mut Cursor,
+        start: u64,
+        target: i64,
+    ) -> Result<Option<Vec<Record>>, Box<dyn Error>> {
+        if self.len < start + 2 {
+            return Ok(None);
+        }
+
+        let (mut low, mut high) = (start, self.len - 1);
+        while low < high {
+            let (first, second) = (low_cursor.get(low)?, high_cursor.get(high)?);
+            // A sum that overflows is past the target on the side of the larger entry's sign.
+            let sum = first.0.checked_add(second.0);
+            let above = sum.map_or(second.0 > 0, |sum| sum > target);
+            let below = sum.map_or(second.0 <= 0, |sum| sum < target);
+            if below {
+                low += 1;
+            } else if above {
+                high -= 1;
+            } else {
+                // Pick the same entries as `ksum::find`: the first two of a run of equal values, or
+                // else the first entry of each run.
+                if first.0 == second.0 {
+                    return Ok(Some(vec![first, low_cursor.get(low + 1)?]));
+                }
+                let mut high_start = high;
+                while high_start > low + 1 && high_cursor.get(high_start - 1)?.0 == second.0 {
+                    high_start -= 1;
+                }
+                return Ok(Some(vec![first, high_cursor.get(high_start)?]));
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// Sorts `entries` and writes them to a new run file, leaving `entries` empty.
+fn write_run(dir: &TempDir, number: usize, entries: &mut Vec<Record>) -> io::Result<File> {
+    entries.sort_unstable();
+    let path = dir.path().join(format!("run{}", number));
+    let mut out = BufWriter::new(File::create(&path)?);
+    for entry in entries.drain(..) {
+        write_record(&mut out, entry)?;
+    }
+    out.flush()?;
+    File::open(path)
+}
+
+/// Merges the sorted `runs` into the file `sorted`, then deletes the runs.
+fn merge(dir: &TempDir, runs: Vec<File>) -> io::Result<()> {
+    let mut out = BufWriter::new(File::create(dir.path().join("sorted"))?);
+    let mut readers: Vec<BufReader<File>> = runs.into_iter().map(BufReader::new).collect();
+    let mut heap = BinaryHeap::new();
+
+    for (run, reader) in readers.iter_mut().enumerate() {
+        if let Some(record) = read_record(reader)? {
+            heap.push(Reverse((record, run)));
+        }
+    }
+    while let Some(Reverse((record, run))) = heap.pop() {
+        write_record(&mut out, record)?;
+        if let Some(next) = read_record(&mut readers[run])? {
+            heap.push(Reverse((next, run)));
+        }
+    }
+
+    for number in 0..readers.len() {
+        std::fs::remove_file(dir.path().join(format!("run{}", number)))?;
+    }
+    out.flush()
+}
+
+/// Random access to the sorted file, caching the block of records around the last one read.
+struct Cursor {
+    file: File,
+    /// Position of the first cached record.
+    start: u64,
+    block: Vec<Record>,
+}
+
+impl Cursor {
+    fn get(&mut self, position: u64) -> io::Result<Record> {
+        let cached = self.start..self.start + self.block.len() as u64;
+        if !cached.contains(&position) {
+            self.start = position - position % BLOCK;
+            self.file.seek(SeekFrom::Start(self.start * RECORD_BYTES))?;
+            let mut bytes = Vec::with_capacity((BLOCK * RECORD_BYTES) as usize);
+            (&mut self.file)
+                .take(BLOCK * RECORD_BYTES)
+                .read_to_end(&mut bytes)?;
+            self.block = bytes
+                .chunks_exact(RECORD_BYTES as usize)
+                .map(decode)
+                .collect();
+        }
+
+        self.block
+            .get((position - self.start) as usize)
+            .copied()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::UnexpectedEof, "sorted report is short"))
+    }
+}
+
+/// Writes a report of `count` entries for trying out the external search. The entries of the
+/// worked example are scattered through it and every other entry is larger than 2020, so its
+/// answers are the example's.
+pub fn generate(path: &Path, count: u64, seed: u64) -> io::Result<()> {
+    const EXAMPLE: [i64; 6] = [1721, 979, 366, 299, 675, 1456];
+
+    // xorshift64; the seed must not be zero.
+    let mut state = seed.max(1);
+    let mut next = move || {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state
+    };
+
+    let count = count.max(EXAMPLE.len() as u64);
+    let mut planted: Vec<u64> = Vec::new();
+    while planted.len() < EXAMPLE.len() {
+        let line = next() % count;
+        if !planted.contains(&line) {
+            planted.push(line);
+        }
+    }
+
+    let mut out = BufWriter::new(File::create(path)?);
+    for line in 0..count {
+        let value = match planted.iter().position(|planted| *planted == line) {
+            Some(example) => EXAMPLE[example],
+            None => 2021 + (next() % 1_000_000_000) as i64,
+        };
+        writeln!(out, "{}", value)?;
+    }
+    out.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ksum;
+
+    #[test]
+    fn matches_in_memory_search_across_many_runs() {
+        let report = "1721\n979\n366\n299\n675\n1456\n1010\n1010\n";
+        let entries = crate::parse(report).unwrap();
+        let sorted = SortedReport::sort(report.as_bytes(), 3).unwrap();
+        assert_eq!(sorted.len(), 8);
+
+        for k in [2, 3].iter() {
+            assert_eq!(
+                sorted.find(*k, 2020).unwrap(),
+                ksum::find(&entries, *k, 2020)
+            );
+        }
+
+        // With values repeated, both searches must pick the same entries of each run.
+        let repeated = "1721\n299\n1721\n979\n1010\n299\n366\n1010\n675\n1010\n979\n";
+        let entries = crate::parse(repeated).unwrap();
+        let sorted = SortedReport::sort(repeated.as_bytes(), 2).unwrap();
+        for (k, target) in [(2, 2020), (2, 598), (2, 2700), (3, 2020)].iter() {
+            assert_eq!(
+                sorted.find(*k, *target).unwrap(),
+                ksum::find(&entries, *k, *target),
+                "{} entries summing to {}",
+                k,
+                target
+            );
+        }
+        let doubled = SortedReport::sort("3\n1010\n1010\n".as_bytes(), 1).unwrap();
+        assert_eq!(doubled.find(2, 2020).unwrap().unwrap().indices, [1, 2]);
+        assert_eq!(sorted.find(3, 1).unwrap(), None);
+        assert!(sorted.find(4, 2020).is_err());
+
+        let large = format!("1\n2\n{}\n{}\n{}\n", i64::MAX, i64::MIN, i64::MAX - 3);
+        let large = SortedReport::sort(large.as_bytes(), 2).unwrap();
+        assert_eq!(large.find(2, 3).unwrap().unwrap().values, [1, 2]);
+        assert_eq!(
+            large.find(2, i64::MIN + 1).unwrap().unwrap().indices,
+            [0, 3]
+        );
+        assert_eq!(large.find(3, i64::MAX).unwrap().unwrap().indices, [0, 1, 4]);
+
+        let err = SortedReport::sort("1\nx\n".as_bytes(), 1).err().unwrap();
+        assert_eq!(err.to_string(), "2:1: expected a number, found \"x\"");
+    }
+
+    #[test]
+    fn solves_generated_reports() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("report.txt");
+        generate(&path, 20_000, 7).unwrap();
+
+        let runs = tempfile::tempdir().unwrap();
+        let sorted = SortedReport::from_file_in(&path, 1_000, runs.path()).unwrap();
+        assert_eq!(std::fs::read_dir(runs.path()).unwrap().count(), 1);
+        let pair = sorted.find(2, 2020).unwrap().unwrap();
+        assert_eq!(pair.product(), Ok(514579));
+        let triple = sorted.find(3, 2020).unwrap().unwrap();
+        assert_eq!(triple.product(), Ok(241861950));
+    }
+}
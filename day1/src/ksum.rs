@@ -0,0 +1,335 @@
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt;
+
+use num_bigint::BigInt;
+
+/// An integer type the solver can search over. Every operation reports overflow as `None`.
+pub trait Number: Clone + Ord + fmt::Display {
+    fn zero() -> Self;
+    fn one() -> Self;
+    /// `count` as this type, or `None` if it does not fit.
+    fn from_count(count: usize) -> Option<Self>;
+    fn checked_add(&self, other: &Self) -> Option<Self>;
+    fn checked_sub(&self, other: &Self) -> Option<Self>;
+    fn checked_mul(&self, other: &Self) -> Option<Self>;
+}
+
+macro_rules! primitive_number {
+    ($($t:ty),*) => {
+        $(
+            impl Number for $t {
+                fn zero() -> $t {
+                    0
+                }
+
+                fn one() -> $t {
+                    1
+                }
+
+                fn from_count(count: usize) -> Option<$t> {
+                    <$t>::try_from(count).ok()
+                }
+
+                fn checked_add(&self, other: &$t) -> Option<$t> {
+                    <$t>::checked_add(*self, *other)
+                }
+
+                fn checked_sub(&self, other: &$t) -> Option<$t> {
+                    <$t>::checked_sub(*self, *other)
+                }
+
+                fn checked_mul(&self, other: &$t) -> Option<$t> {
+                    <$t>::checked_mul(*self, *other)
+                }
+            }
+        )*
+    };
+}
+
+primitive_number!(i32, i64, i128);
+
+impl Number for BigInt {
+    fn zero() -> BigInt {
+        BigInt::from(0)
+    }
+
+    fn one() -> BigInt {
+        BigInt::from(1)
+    }
+
+    fn from_count(count: usize) -> Option<BigInt> {
+        Some(BigInt::from(count))
+    }
+
+    fn checked_add(&self, other: &BigInt) -> Option<BigInt> {
+        Some(self + other)
+    }
+
+    fn checked_sub(&self, other: &BigInt) -> Option<BigInt> {
+        Some(self - other)
+    }
+
+    fn checked_mul(&self, other: &BigInt) -> Option<BigInt> {
+        Some(self * other)
+    }
+}
+
+/// A sum or product that does not fit the entries' integer type.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Overflow;
+
+impl fmt::Display for Overflow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "arithmetic overflow; try a wider integer type")
+    }
+}
+
+impl Error for Overflow {}
+
+/// `k` entries at distinct positions of the expense report, in report order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Combination<T> {
+    /// 0-based positions in the report.
+    pub indices: Vec<usize>,
+    pub values: Vec<T>,
+}
+
+impl<T: Number> Combination<T> {
+    pub fn product(&self) -> Result<T, Overflow> {
+        self.values
+            .iter()
+            .try_fold(T::one(), |product, value| product.checked_mul(value))
+            .ok_or(Overflow)
+    }
+}
+
+/// The first combination of `k` entries summing to `target`, or `None` if there is none. An entry
+/// is never used twice, though two entries may hold the same value.
+pub fn find<T: Number>(entries: &[T], k: usize, target: T) -> Option<Combination<T>> {
+    solve(entries, k, target, true).pop()
+}
+
+/// Every combination of `k` entries summing to `target`, ordered by their positions.
+pub fn find_all<T: Number>(entries: &[T], k: usize, target: T) -> Vec<Combination<T>> {
+    let mut combinations = solve(entries, k, target, false);
+    combinations.sort_by(|a, b| a.indices.cmp(&b.indices));
+    combinations
+}
+
+fn solve<T: Number>(entries: &[T], k: usize, target: T, first_only: bool) -> Vec<Combination<T>> {
+    let mut sorted: Vec<(&T, usize)> = entries
+        .iter()
+        .enumerate()
+        .map(|(index, value)| (value, index))
+        .collect();
+    sorted.sort_unstable();
+
+    let mut search = Search {
+        sorted: &sorted,
+        first_only,
+        chosen: Vec::with_capacity(k),
+        found: Vec::new(),
+    };
+    search.run(0, k, &target);
+
+    search
+        .found
+        .into_iter()
+        .map(|mut indices| {
+            indices.sort_unstable();
+            let values = indices
+                .iter()
+                .map(|index| entries[*index].clone())
+                .collect();
+            Combination { indices, values }
+        })
+        .collect()
+}
+
+/// Picks entries in ascending value order: one at a time while more than two are needed, then
+/// the last two with a pair of pointers closing in from both ends.
+struct Search<'a, T> {
+    /// Values with their original positions, sorted by value.
+    sorted: &'a [(&'a T, usize)],
+    first_only: bool,
+    chosen: Vec<usize>,
+    found: Vec<Vec<usize>>,
+}
+
+impl<'a, T: Number> Search<'a, T> {
+    fn done(&self) -> bool {
+        self.first_only && !self.found.is_empty()
+    }
+
+    fn record(&mut self, last: &[usize]) {
+        let mut indices = self.chosen.clone();
+        indices.extend(last.iter().map(|position| self.sorted[*position].1));
+        self.found.push(indices);
+    }
+
+    fn run(&mut self, start: usize, k: usize, target: &T) {
+        let remaining = self.sorted.len() - start;
+        match k {
+            _ if k > remaining => {}
+            0 => {
+                if *target == T::zero() {
+                    self.record(&[]);
+                }
+            }
+            1 => {
+                for position in start..self.sorted.len() {
+                    if self.sorted[position].0 == target {
+                        self.record(&[position]);
+                        if self.done() {
+                            break;
+                        }
+                    }
+                }
+            }
+            2 => self.pairs(start, target),
+            _ => {
+                for position in start..=self.sorted.len() - k {
+                    let value = self.sorted[position].0;
+                    // Everything after this entry is at least as large, so the sum only grows. A
+                    // positive value whose multiple overflows is certainly past any target.
+                    let smallest_sum = T::from_count(k).and_then(|k| value.checked_mul(&k));
+                    match smallest_sum {
+                        Some(sum) if sum > *target => break,
+                        None if *value > T::zero() => break,
+                        _ => {}
+                    }
+
+                    // Only a negative entry can push the rest past the largest value, and the
+                    // others would then have to sum to more than the type holds; skip it.
+                    let rest = match target.checked_sub(value) {
+                        Some(rest) => rest,
+                        None => continue,
+                    };
+                    self.chosen.push(self.sorted[position].1);
+                    self.run(position + 1, k - 1, &rest);
+                    self.chosen.pop();
+                    if self.done() {
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
+    fn pairs(&mut self, start: usize, target: &T) {
+        let sorted = self.sorted;
+        let value = |position: usize| sorted[position].0;
+        let (mut low, mut high) = (start, self.sorted.len() - 1);
+
+        while low < high {
+            // A sum can only overflow upwards if the larger entry is positive, and downwards if
+            // it is not; either way it is past the target on that side.
+            let sum = value(low).checked_add(value(high));
+            let above = match &sum {
+                Some(sum) => sum > target,
+                None => *value(high) > T::zero(),
+            };
+            let below = match &sum {
+                Some(sum) => sum < target,
+                None => !above,
+            };
+            if below {
+                low += 1;
+            } else if above {
+                high -= 1;
+            } else if value(low) == value(high) {
+                // Every entry from `low` to `high` holds the same value, so any two of them pair.
+                for first in low..high {
+                    for second in first + 1..=high {
+                        self.record(&[first, second]);
+                        if self.done() {
+                            return;
+                        }
+                    }
+                }
+                return;
+            } else {
+                let low_end = (low..=high).find(|p| value(*p) != value(low)).unwrap();
+                let high_start = (low..=high)
+                    .rev()
+                    .find(|p| value(*p) != value(high))
+                    .unwrap()
+                    + 1;
+                for first in low..low_end {
+                    for second in high_start..=high {
+                        self.record(&[first, second]);
+                        if self.done() {
+                            return;
+                        }
+                    }
+                }
+                low = low_end;
+                high = high_start - 1;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_distinct_entries() {
+        let report = [1721, 979, 366, 299, 675, 1456];
+        let pair = find(&report, 2, 2020).unwrap();
+        assert_eq!((pair.indices, pair.values), (vec![0, 3], vec![1721, 299]));
+        let triple = find(&report, 3, 2020).unwrap();
+        assert_eq!(triple.values, [979, 366, 675]);
+        assert_eq!(triple.product(), Ok(241861950));
+
+        // 673 + 673 + 674 would sum to 2020, but there is only one 673.
+        assert_eq!(find(&[673, 674, 5], 3, 2020), None);
+        assert_eq!(find(&[673, 674, 673], 3, 2020).unwrap().indices, [0, 1, 2]);
+        assert_eq!(find(&[1010, 3], 2, 2020), None);
+        assert_eq!(find(&[3, 1010, 1010], 2, 2020).unwrap().indices, [1, 2]);
+        assert_eq!(find(&[1, 2], 3, 3), None);
+    }
+
+    #[test]
+    fn enumerates_every_solution() {
+        let all = find_all(&[5, 5, 5, 0, 10], 2, 10);
+        let indices: Vec<Vec<usize>> = all.into_iter().map(|c| c.indices).collect();
+        assert_eq!(indices, [vec![0, 1], vec![0, 2], vec![1, 2], vec![3, 4]]);
+
+        let all = find_all(&[1, 2, 3, 4, 5, -1], 3, 6);
+        let values: Vec<Vec<i32>> = all.into_iter().map(|c| c.values).collect();
+        assert_eq!(values, [vec![1, 2, 3], vec![2, 5, -1], vec![3, 4, -1]]);
+        assert_eq!(find_all(&[7], 1, 7).len(), 1);
+        assert_eq!(find_all::<i32>(&[], 0, 0).len(), 1);
+    }
+
+    #[test]
+    fn searches_past_overflowing_sums() {
+        let big = 3_000_000_000_i64;
+        let triple = find(&[big, big, big], 3, 3 * big).unwrap();
+        assert_eq!(triple.product(), Err(Overflow));
+        assert_eq!(find(&[i32::MAX, 1], 2, 0), None);
+        assert_eq!(find(&[1i32, 2, i32::MAX], 2, 3).unwrap().values, [1, 2]);
+        assert_eq!(find(&[i32::MIN, -2, -1], 2, -3).unwrap().values, [-2, -1]);
+        assert_eq!(
+            find(&[i32::MIN, 1, 2, i32::MAX - 3], 3, i32::MAX)
+                .unwrap()
+                .indices,
+            [1, 2, 3]
+        );
+
+        let wide: Vec<i128> = vec![big.into(); 3];
+        assert_eq!(
+            find(&wide, 3, 3 * i128::from(big)).unwrap().product(),
+            Ok(27_000_000_000_000_000_000_000_000_000)
+        );
+        let huge: Vec<BigInt> = vec![BigInt::from(i128::MAX); 2];
+        let pair = find(&huge, 2, BigInt::from(i128::MAX) * 2).unwrap();
+        assert_eq!(
+            pair.product().unwrap(),
+            BigInt::from(i128::MAX) * BigInt::from(i128::MAX)
+        );
+    }
+}
@@ -0,0 +1,83 @@
+use std::error::Error;
+
+use common::{parse_lines, parse_number, Answer, ParseError, Solution};
+use ksum::{Combination, Overflow};
+
+pub mod external;
+pub mod ksum;
+
+/// What the entries the Elves are looking for add up to.
+pub const TARGET: i64 = 2020;
+
+pub struct Day1;
+
+impl Solution for Day1 {
+    const DAY: u8 = 1;
+    const EXAMPLE: &'static str = include_str!("../fixtures/example.txt");
+    type Input = Vec<i64>;
+
+    fn parse(&self, input: &str) -> Result<Vec<i64>, Box<dyn Error>> {
+        Ok(parse(input)?)
+    }
+
+    fn part1(&self, entries: &Vec<i64>) -> Result<Answer, Box<dyn Error>> {
+        let pair = ksum::find(entries, 2, TARGET).ok_or("no two entries sum to 2020")?;
+        answer(&pair)
+    }
+
+    fn part2(&self, entries: &Vec<i64>) -> Result<Answer, Box<dyn Error>> {
+        let triple = ksum::find(entries, 3, TARGET).ok_or("no three entries sum to 2020")?;
+        answer(&triple)
+    }
+}
+
+/// The product of the entries, along with the entries and their 1-based line numbers.
+fn answer(combination: &Combination<i64>) -> Result<Answer, Box<dyn Error>> {
+    let join = |items: Vec<String>| items.join(" ");
+    Ok(Answer::new(combination.product()?)
+        .with_detail(
+            "entries",
+            join(combination.values.iter().map(i64::to_string).collect()),
+        )
+        .with_detail(
+            "lines",
+            join(
+                combination
+                    .indices
+                    .iter()
+                    .map(|index| (index + 1).to_string())
+                    .collect(),
+            ),
+        ))
+}
+
+pub fn parse(input_as_str: &str) -> Result<Vec<i64>, ParseError> {
+    parse_lines(input_as_str, |line| parse_number(line, 1))
+}
+
+/// Product of the two entries that sum to 2020.
+pub fn part1(entries: &[i64]) -> Result<Option<i64>, Overflow> {
+    ksum::find(entries, 2, TARGET)
+        .map(|pair| pair.product())
+        .transpose()
+}
+
+/// Product of the three entries that sum to 2020.
+pub fn part2(entries: &[i64]) -> Result<Option<i64>, Overflow> {
+    ksum::find(entries, 3, TARGET)
+        .map(|triple| triple.product())
+        .transpose()
+}
+
+common::fixture_tests!(Day1);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_bad_entries() {
+        let err = parse("1721\n97x\n").unwrap_err();
+        assert_eq!(err.to_string(), "2:1: expected a number, found \"97x\"");
+    }
+}
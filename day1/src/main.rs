@@ -1,54 +1,66 @@
-use std::collections::HashSet;
+use std::env;
 use std::error::Error;
-use std::fs;
+use std::path::Path;
+use std::process;
 
-fn main() -> Result<(), Box<dyn Error>> {
-    let input_file = &fs::read("input.txt")?;
-    let input_as_str = String::from_utf8_lossy(&input_file);
-    part1(&input_as_str)?;
-    part2(&input_as_str)
-}
-
-fn part1(input_as_str: &std::borrow::Cow<str>) -> Result<(), Box<dyn Error>> {
-    let input_lines = input_as_str.lines();
+use common::ParseError;
+use day1::external::{self, SortedReport};
 
-    const TARGET_YEAR: i32 = 2020;
-
-    let mut previous_values = HashSet::new();
-    for i in input_lines {
-        let line_val: i32 = i.parse()?;
-        let target_val = TARGET_YEAR - line_val;
-
-        if previous_values.contains(&target_val) {
-            let prod = line_val * target_val;
-            println!("Part 1: values ({}, {}): {}", line_val, target_val, prod);
-            break;
-        }
+/// `day1 external [--tmp-dir <dir>] <report> [<chunk>]` solves a report too large for memory by
+/// sorting it on disk, under `dir` if given and the system's temporary directory otherwise.
+/// `day1 generate <report> <count> [<seed>]` writes such a report. Anything else is the usual day
+/// binary.
+fn main() -> Result<(), Box<dyn Error>> {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let result = match args.first().map(String::as_str) {
+        Some("external") => external_main(&args[1..]),
+        Some("generate") => generate_main(&args[1..]),
+        _ => return common::run_main(&day1::Day1),
+    };
 
-        previous_values.insert(line_val);
+    if let Err(err) = result {
+        eprintln!("error: {}", err);
+        process::exit(1);
     }
-
     Ok(())
 }
 
-fn part2(input_as_str: &std::borrow::Cow<str>) -> Result<(), Box<dyn Error>> {
-    let input_lines = input_as_str.lines();
-
-    const TARGET_YEAR: i32 = 2020;
-
-    let value_set: HashSet<i32> = input_lines.map(|i| i.parse::<i32>().unwrap()).collect();
-    for i in &value_set {
-        let new_target_year = TARGET_YEAR - i;
+fn external_main(args: &[String]) -> Result<(), Box<dyn Error>> {
+    let usage = "usage: day1 external [--tmp-dir <dir>] <report> [<chunk>]";
+    let (tmp_dir, args) = match args {
+        [flag, dir, rest @ ..] if flag == "--tmp-dir" => (Some(Path::new(dir)), rest),
+        _ => (None, args),
+    };
+    let path = args.first().ok_or(usage)?;
+    let chunk = match args.get(1) {
+        Some(chunk) => chunk.parse()?,
+        None => external::DEFAULT_CHUNK,
+    };
 
-        for j in &value_set {
-            let target_val = new_target_year - j;
-            if value_set.contains(&target_val) {
-                let prod = i * j * target_val;
-                println!("Part2: values ({}, {}, {}): {}", i, j, target_val, prod);
-                return Ok(());
-            }
-        }
+    let report = match tmp_dir {
+        Some(dir) => SortedReport::from_file_in(Path::new(path), chunk, dir),
+        None => SortedReport::from_file(Path::new(path), chunk),
+    };
+    let report = report.map_err(|err| match err.downcast_ref::<ParseError>() {
+        Some(_) => format!("{}:{}", path, err).into(),
+        None => err,
+    })?;
+    for (part, k) in [(1, 2), (2, 3)].iter() {
+        let found = report.find(*k, day1::TARGET)?;
+        let entries = found.ok_or(format!("no {} entries sum to 2020", k))?;
+        println!("Part{}: {}", part, entries.product()?);
     }
+    Ok(())
+}
 
+fn generate_main(args: &[String]) -> Result<(), Box<dyn Error>> {
+    let usage = "usage: day1 generate <report> <count> [<seed>]";
+    let path = args.first().ok_or(usage)?;
+    let count = args.get(1).ok_or(usage)?.parse()?;
+    let seed = match args.get(2) {
+        Some(seed) => seed.parse()?,
+        None => 1,
+    };
+    external::generate(Path::new(path), count, seed)?;
     Ok(())
 }
@@ -0,0 +1,161 @@
+use std::error::Error;
+
+use common::{Answer, ParseError, Solution};
+use grid::{Cell, Grid, Pos, DIRECTIONS8};
+
+pub struct Day11;
+
+impl Solution for Day11 {
+    const DAY: u8 = 11;
+    const EXAMPLE: &'static str = include_str!("../fixtures/example.txt");
+    type Input = Board;
+
+    fn parse(&self, input: &str) -> Result<Board, Box<dyn Error>> {
+        Ok(to_board(input)?)
+    }
+
+    fn part1(&self, board: &Board) -> Result<Answer, Box<dyn Error>> {
+        Ok(Answer::new(part1(board)))
+    }
+
+    fn part2(&self, board: &Board) -> Result<Answer, Box<dyn Error>> {
+        Ok(Answer::new(part2(board)))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SeatState {
+    Floor,
+    Empty,
+    Filled,
+}
+
+impl Cell for SeatState {
+    fn from_char(c: char) -> Option<SeatState> {
+        match c {
+            '.' => Some(SeatState::Floor),
+            'L' => Some(SeatState::Empty),
+            '#' => Some(SeatState::Filled),
+            _ => None,
+        }
+    }
+
+    fn to_char(&self) -> char {
+        match self {
+            SeatState::Floor => '.',
+            SeatState::Empty => 'L',
+            SeatState::Filled => '#',
+        }
+    }
+}
+
+pub type Board = Grid<SeatState>;
+
+/// Reads the seat layout, one row of seats per line.
+pub fn to_board(input_as_str: &str) -> Result<Board, ParseError> {
+    Ok(Grid::parse(input_as_str)?)
+}
+
+fn adjacent_filled(state: &Board, pos: Pos) -> usize {
+    state
+        .neighbours8(pos)
+        .filter(|neighbour| state[*neighbour] == SeatState::Filled)
+        .count()
+}
+
+fn can_see(state: &Board, pos: Pos) -> usize {
+    DIRECTIONS8
+        .iter()
+        .filter(|direction| {
+            state
+                .ray(pos, **direction)
+                .map(|seen| state[seen])
+                .find(|seat| *seat != SeatState::Floor)
+                == Some(SeatState::Filled)
+        })
+        .count()
+}
+
+fn next_frame(
+    current: &Board,
+    neighbor_calculator: fn(&Board, Pos) -> usize,
+    will_vacate: fn(usize) -> bool,
+) -> Option<Board> {
+    let mut next = current.clone();
+    let mut changed = false;
+
+    for pos in current.positions() {
+        let seat = current[pos];
+        let new = match seat {
+            SeatState::Empty => match neighbor_calculator(current, pos) {
+                0 => SeatState::Filled,
+                _ => seat,
+            },
+            SeatState::Filled => {
+                if will_vacate(neighbor_calculator(current, pos)) {
+                    SeatState::Empty
+                } else {
+                    seat
+                }
+            }
+            _ => seat,
+        };
+
+        if new != seat {
+            changed = true;
+        }
+
+        next[pos] = new;
+    }
+    match changed {
+        true => Some(next),
+        false => None,
+    }
+}
+
+fn filled(board: &Board) -> usize {
+    board
+        .iter()
+        .filter(|state| matches!(state, SeatState::Filled))
+        .count()
+}
+
+/// Occupied seats once the layout settles, counting the eight adjacent seats.
+pub fn part1(board: &Board) -> usize {
+    let mut board = board.clone();
+    while let Some(next) = next_frame(&board, adjacent_filled, |adjacent| adjacent >= 4) {
+        board = next;
+    }
+
+    filled(&board)
+}
+
+/// Occupied seats once the layout settles, counting the first seat visible in each direction.
+pub fn part2(board: &Board) -> usize {
+    let mut board = board.clone();
+    while let Some(next) = next_frame(&board, can_see, |visible| visible >= 5) {
+        board = next;
+    }
+
+    filled(&board)
+}
+
+common::fixture_tests!(Day11);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_the_board_it_parsed() {
+        let board = to_board(Day11::EXAMPLE).unwrap();
+        assert_eq!(board.height(), 10);
+        assert_eq!(board.render(), Day11::EXAMPLE);
+    }
+
+    #[test]
+    fn rejects_unknown_seats() {
+        let err = to_board("L.L\nL#x\n").unwrap_err();
+        assert_eq!(err.to_string(), "2:3: unexpected 'x'");
+    }
+}
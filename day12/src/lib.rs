@@ -0,0 +1,228 @@
+use std::error::Error;
+use std::fmt::Display;
+
+use common::{parse_lines, parse_number, Answer, ParseError, Solution};
+
+pub struct Day12;
+
+impl Solution for Day12 {
+    const DAY: u8 = 12;
+    const EXAMPLE: &'static str = include_str!("../fixtures/example.txt");
+    type Input = Vec<Command>;
+
+    fn parse(&self, input: &str) -> Result<Vec<Command>, Box<dyn Error>> {
+        Ok(parse_commands(input)?)
+    }
+
+    fn part1(&self, commands: &Vec<Command>) -> Result<Answer, Box<dyn Error>> {
+        Ok(Answer::new(part1(commands)))
+    }
+
+    fn part2(&self, commands: &Vec<Command>) -> Result<Answer, Box<dyn Error>> {
+        Ok(Answer::new(part2(commands)))
+    }
+}
+
+/// Reads the navigation instructions, one action and value per line. Turns must be whole
+/// quarter turns.
+pub fn parse_commands(input_as_str: &str) -> Result<Vec<Command>, ParseError> {
+    parse_lines(input_as_str, |line| {
+        let cmd = line
+            .chars()
+            .next()
+            .ok_or_else(|| ParseError::new("expected an action"))?;
+        let amount: i32 = parse_number(&line[cmd.len_utf8()..], 2)?;
+        let command = match cmd {
+            'N' => Command::North(amount),
+            'S' => Command::South(amount),
+            'E' => Command::East(amount),
+            'W' => Command::West(amount),
+            'L' => Command::Left(amount),
+            'R' => Command::Right(amount),
+            'F' => Command::Forward(amount),
+            _ => {
+                return Err(ParseError::new(format!("unknown action {:?}", cmd)).at_column(1));
+            }
+        };
+
+        match command {
+            Command::Left(deg) | Command::Right(deg) if deg < 0 || deg % 90 != 0 => {
+                Err(ParseError::new(format!("cannot turn by {} degrees", deg)).at_column(2))
+            }
+            _ => Ok(command),
+        }
+    })
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum Direction {
+    North,
+    South,
+    East,
+    West,
+}
+
+impl Display for Direction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::result::Result<(), std::fmt::Error> {
+        write!(
+            f,
+            "{}",
+            match self {
+                Direction::North => "N",
+                Direction::South => "S",
+                Direction::East => "E",
+                Direction::West => "W",
+            }
+        )
+    }
+}
+
+impl Direction {
+    fn rotated(&self, degrees: i32) -> Direction {
+        let to_rotate = degrees % 360;
+        let rotations = to_rotate / 90;
+
+        let directions = [
+            Direction::North,
+            Direction::East,
+            Direction::South,
+            Direction::West,
+        ];
+        let cur_pos = directions.iter().position(|d| d == self).unwrap();
+
+        let new_pos = (cur_pos + (rotations as usize)) % 4;
+
+        directions[new_pos]
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Command {
+    North(i32),
+    South(i32),
+    East(i32),
+    West(i32),
+    Left(i32),
+    Right(i32),
+    Forward(i32),
+}
+
+struct Point {
+    x: i32,
+    y: i32,
+}
+
+impl Point {
+    fn apply(&mut self, cmd: &Command) {
+        match cmd {
+            Command::North(n) => self.y += n,
+            Command::South(n) => self.y -= n,
+            Command::East(n) => self.x += n,
+            Command::West(n) => self.x -= n,
+            Command::Left(deg) => self.apply(&Command::Right(360 - *deg % 360)),
+            Command::Right(deg) => {
+                let real_deg = deg % 360;
+                let rotations = real_deg / 90;
+                for _ in 0..rotations {
+                    let prev_x = self.x;
+                    self.x = self.y;
+                    self.y = -prev_x;
+                }
+            }
+            Command::Forward(_) => unreachable!("points do not move forward"),
+        }
+    }
+}
+
+struct Location {
+    point: Point,
+    facing: Direction,
+}
+
+impl Location {
+    fn apply(&mut self, cmd: &Command) {
+        match cmd {
+            Command::North(_) | Command::South(_) | Command::East(_) | Command::West(_) => {
+                self.point.apply(cmd)
+            }
+            Command::Left(deg) => self.facing = self.facing.rotated(360 - *deg % 360),
+            Command::Right(deg) => self.facing = self.facing.rotated(*deg),
+            Command::Forward(distance) => self.apply(&match self.facing {
+                Direction::North => Command::North(*distance),
+                Direction::South => Command::South(*distance),
+                Direction::East => Command::East(*distance),
+                Direction::West => Command::West(*distance),
+            }),
+        }
+    }
+}
+
+struct Location2 {
+    point: Point,
+    waypoint: Point,
+}
+
+impl Location2 {
+    fn apply(&mut self, cmd: &Command) {
+        match cmd {
+            Command::Forward(distance) => {
+                let to_move_x = self.waypoint.x * distance;
+                let to_move_y = self.waypoint.y * distance;
+                self.point.x += to_move_x;
+                self.point.y += to_move_y;
+            }
+            _ => self.waypoint.apply(cmd),
+        }
+    }
+}
+
+/// Manhattan distance travelled when the actions move the ship itself.
+pub fn part1(commands: &[Command]) -> i32 {
+    let mut position = Location {
+        point: Point { x: 0, y: 0 },
+        facing: Direction::East,
+    };
+
+    for cmd in commands {
+        position.apply(cmd);
+    }
+    position.point.x.abs() + position.point.y.abs()
+}
+
+/// Manhattan distance travelled when the actions move a waypoint relative to the ship.
+pub fn part2(commands: &[Command]) -> i32 {
+    let mut position = Location2 {
+        point: Point { x: 0, y: 0 },
+        waypoint: Point { x: 10, y: 1 },
+    };
+
+    for cmd in commands {
+        position.apply(cmd);
+    }
+    position.point.x.abs() + position.point.y.abs()
+}
+
+common::fixture_tests!(Day12);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_commands() {
+        let commands = parse_commands(Day12::EXAMPLE).unwrap();
+        assert_eq!(commands[0], Command::Forward(10));
+        assert_eq!(commands[3], Command::Right(90));
+    }
+
+    #[test]
+    fn rejects_bad_commands() {
+        let err = parse_commands("F10\nX3\n").unwrap_err();
+        assert_eq!(err.to_string(), "2:1: unknown action 'X'");
+        let err = parse_commands("F10\n\n").unwrap_err();
+        assert_eq!(err.line, Some(2));
+        let err = parse_commands("R45").unwrap_err();
+        assert_eq!(err.to_string(), "1:2: cannot turn by 45 degrees");
+        assert_eq!(part1(&parse_commands("L450\nF1").unwrap()), 1);
+    }
+}
@@ -0,0 +1,139 @@
+use std::error::Error;
+
+use common::{parse_lines, parse_number, Answer, ParseError, Solution};
+use policy::Policy;
+use unicode_segmentation::UnicodeSegmentation;
+
+pub mod policy;
+pub mod report;
+
+pub struct Day2;
+
+impl Solution for Day2 {
+    const DAY: u8 = 2;
+    const EXAMPLE: &'static str = include_str!("../fixtures/example.txt");
+    type Input = Vec<Entry>;
+
+    fn parse(&self, input: &str) -> Result<Vec<Entry>, Box<dyn Error>> {
+        Ok(parse(input)?)
+    }
+
+    fn part1(&self, entries: &Vec<Entry>) -> Result<Answer, Box<dyn Error>> {
+        Ok(Answer::new(part1(entries)))
+    }
+
+    fn part2(&self, entries: &Vec<Entry>) -> Result<Answer, Box<dyn Error>> {
+        Ok(Answer::new(part2(entries)))
+    }
+}
+
+/// One line of the password database: `first-second letter: password`. The letter is a single
+/// grapheme and the password any run of non-whitespace, in any script.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entry {
+    pub first: usize,
+    pub second: usize,
+    pub letter: String,
+    pub password: String,
+}
+
+pub fn parse(input_as_str: &str) -> Result<Vec<Entry>, ParseError> {
+    parse_lines(input_as_str, parse_entry)
+}
+
+/// Parses one database line, reporting the 1-based column, counted in characters, where it stops
+/// following the format.
+fn parse_entry(line: &str) -> Result<Entry, ParseError> {
+    let column = |offset: usize| line[..offset].chars().count() + 1;
+    let error = |message: &str, offset| Err(ParseError::new(message).at_column(column(offset)));
+
+    let (range, rest) = match line.split_once(' ') {
+        Some(split) => split,
+        None => return error("expected \"<first>-<second> <letter>: <password>\"", 0),
+    };
+    let (first, second) = match range.split_once('-') {
+        Some(split) => split,
+        None => return error("expected a range such as \"1-3\"", 0),
+    };
+    let letter_offset = range.len() + 1;
+    let (letter, password) = match rest.split_once(": ") {
+        Some(split) => split,
+        None => return error("expected \"<letter>: <password>\"", letter_offset),
+    };
+    let password_offset = letter_offset + letter.len() + 2;
+    if letter.graphemes(true).count() != 1 {
+        return error("expected a single letter", letter_offset);
+    }
+    if password.is_empty() {
+        return error("expected a password", password_offset);
+    }
+    if let Some(space) = password.find(char::is_whitespace) {
+        return error(
+            "passwords cannot contain whitespace",
+            password_offset + space,
+        );
+    }
+
+    Ok(Entry {
+        first: parse_number(first, 1)?,
+        second: parse_number(second, first.len() + 2)?,
+        letter: letter.to_string(),
+        password: password.to_string(),
+    })
+}
+
+/// Number of passwords where the letter appears between `first` and `second` times.
+pub fn part1(entries: &[Entry]) -> usize {
+    builtin(policy::SLED_RENTAL).count_valid(entries)
+}
+
+/// Number of passwords where the letter is at exactly one of the two (1-based) positions.
+pub fn part2(entries: &[Entry]) -> usize {
+    builtin(policy::TOBOGGAN).count_valid(entries)
+}
+
+fn builtin(policy: &str) -> Policy {
+    Policy::parse(policy).expect("built-in policies are valid")
+}
+
+common::fixture_tests!(Day2);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_entries() {
+        let entries = parse(Day2::EXAMPLE).unwrap();
+        assert_eq!(
+            entries[0],
+            Entry {
+                first: 1,
+                second: 3,
+                letter: "a".to_string(),
+                password: "abcde".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_lines() {
+        let err = parse("1-3 a: abcde\n1-3 b cdefg\n").unwrap_err();
+        assert_eq!((err.line, err.column), (Some(2), Some(5)));
+        let column = |line| parse(line).unwrap_err().column;
+        assert_eq!(column("1:3 a: abc"), Some(1));
+        assert_eq!(column("1-x a: abc"), Some(3));
+        assert_eq!(column("1-3 ab: abc"), Some(5));
+        assert_eq!(column("1-3 é: ab c"), Some(10));
+        assert_eq!(part2(&parse("0-9 a: a").unwrap()), 0);
+    }
+
+    #[test]
+    fn counts_graphemes_in_any_script() {
+        // "e" followed by a combining acute accent is one letter, as is the precomposed "é".
+        let entries = parse("1-2 e\u{301}: e\u{301}xe\n2-2 ж: жжы\n1-3 é: xéé").unwrap();
+        assert_eq!(entries[0].letter, "e\u{301}");
+        assert_eq!(part1(&entries), 3);
+        assert_eq!(part2(&entries), 2);
+    }
+}
@@ -1,60 +1,89 @@
-use regex::Regex;
+use std::env;
 use std::error::Error;
 use std::fs;
+use std::process;
 
-fn main() -> Result<(), Box<dyn Error>> {
-    let input_file = &fs::read("input.txt")?;
-    let input_as_str = String::from_utf8_lossy(&input_file);
-    part1(&input_as_str)?;
-    part2(&input_as_str)
-}
+use day2::policy::{self, Policy};
+use day2::{report, Entry};
 
-fn part1(input_as_str: &std::borrow::Cow<str>) -> Result<(), Box<dyn Error>> {
-    let re = Regex::new(r"(\d+)-(\d+) ([a-z]): ([a-z]+)")?;
+/// `day2 policy [--ignore-case] <policy> <database>` counts the passwords in the database that
+/// satisfy a policy written in the language of `day2::policy`.
+/// `day2 report [--csv] [--ignore-case] <database> [<policy>...]` lists every entry's verdict
+/// under each policy, by default the two from the puzzle. Anything else is the usual day binary.
+fn main() -> Result<(), Box<dyn Error>> {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let result = match args.first().map(String::as_str) {
+        Some("policy") => policy_main(&args[1..]),
+        Some("report") => report_main(&args[1..]),
+        _ => return common::run_main(&day2::Day2),
+    };
 
-    let mut counter = 0;
+    if let Err(err) = result {
+        eprintln!("error: {}", err);
+        process::exit(1);
+    }
+    Ok(())
+}
 
-    for captures in re.captures_iter(input_as_str) {
-        let min: usize = captures[1].parse()?;
-        let max: usize = captures[2].parse()?;
-        let letter = &captures[3];
-        let password = &captures[4];
+/// Removes `flag` from `args`, returning whether it was there.
+fn take_flag(args: &mut Vec<&String>, flag: &str) -> bool {
+    let before = args.len();
+    args.retain(|arg| *arg != flag);
+    args.len() != before
+}
 
-        let count = password
-            .chars()
-            .filter(|c| c == &letter.chars().nth(0).unwrap())
-            .count();
+fn parse_policy(text: &str, ignore_case: bool) -> Result<Policy, String> {
+    let policy = Policy::parse(text).map_err(|err| format!("<policy>:{}", err.at_line(1)))?;
+    Ok(if ignore_case {
+        policy.ignoring_case()
+    } else {
+        policy
+    })
+}
 
-        if min <= count && count <= max {
-            counter += 1;
-        }
-    }
+fn read_database(path: &str) -> Result<Vec<Entry>, String> {
+    let database = fs::read_to_string(path).map_err(|err| format!("{}: {}", path, err))?;
+    day2::parse(&database).map_err(|err| format!("{}:{}", path, err))
+}
 
-    println!("Part1: {}", counter);
+fn policy_main(args: &[String]) -> Result<(), Box<dyn Error>> {
+    let usage = "usage: day2 policy [--ignore-case] <policy> <database>";
+    let mut args: Vec<&String> = args.iter().collect();
+    let ignore_case = take_flag(&mut args, "--ignore-case");
+    let (text, path) = match args[..] {
+        [text, path] => (text, path),
+        _ => return Err(usage.into()),
+    };
 
+    let policy = parse_policy(text, ignore_case)?;
+    let entries = read_database(path)?;
+    println!("{}", policy.count_valid(&entries));
     Ok(())
 }
 
-fn part2(input_as_str: &std::borrow::Cow<str>) -> Result<(), Box<dyn Error>> {
-    let re = Regex::new(r"(\d+)-(\d+) ([a-z]): ([a-z]+)")?;
-
-    let mut counter = 0;
+fn report_main(args: &[String]) -> Result<(), Box<dyn Error>> {
+    let usage = "usage: day2 report [--csv] [--ignore-case] <database> [<policy>...]";
+    let mut args: Vec<&String> = args.iter().collect();
+    let csv = take_flag(&mut args, "--csv");
+    let ignore_case = take_flag(&mut args, "--ignore-case");
+    let (path, texts) = args.split_first().ok_or(usage)?;
 
-    for captures in re.captures_iter(input_as_str) {
-        let first: usize = captures[1].parse()?;
-        let second: usize = captures[2].parse()?;
-        let letter = &captures[3].chars().nth(0).unwrap();
-        let password = &captures[4];
+    let defaults = [policy::SLED_RENTAL, policy::TOBOGGAN];
+    let texts: Vec<&str> = if texts.is_empty() {
+        defaults.to_vec()
+    } else {
+        texts.iter().map(|text| text.as_str()).collect()
+    };
+    let policies = texts
+        .iter()
+        .map(|text| parse_policy(text, ignore_case))
+        .collect::<Result<Vec<_>, _>>()?;
 
-        let first_is_set = &password.chars().nth(first - 1).unwrap() == letter;
-        let second_is_set = &password.chars().nth(second - 1).unwrap() == letter;
-
-        if first_is_set ^ second_is_set {
-            counter += 1;
-        }
+    let rows = report::report(&read_database(path)?, &policies);
+    if csv {
+        print!("{}", report::to_csv(&rows));
+    } else {
+        print!("{}", report::to_text(&rows));
     }
-
-    println!("Part2: {}", counter);
-
     Ok(())
 }
@@ -0,0 +1,793 @@
+//! Password policies as values, written in a small language. A policy is one of the checks below,
+//! or checks combined with `and`, `or`, `not` and parentheses; `and` binds tighter than `or`.
+//!
+//! - `count(c, min, max)`: `c` appears between `min` and `max` times.
+//! - `one_of(c, p, ...)`: `c` is at exactly one of the 1-based positions.
+//! - `all_of(c, p, ...)`: `c` is at every one of the positions.
+//! - `matches("regex")`: the password matches the regex somewhere.
+//! - `has(class, n)`: at least `n` characters are `lower`, `upper`, `digit`, `alpha`, `alnum` or
+//!   `punct`.
+//!
+//! `ignore_case(policy)` compares letters, and matches regexes, without regard to case.
+//!
+//! Letters are single graphemes written `'a'`, positions count graphemes, and numbers are in
+//! decimal. `letter`, `first` and `second` stand for the fields of the database entry being
+//! checked, so the two official policies are `count(letter, first, second)` and
+//! `one_of(letter, first, second)`.
+
+use std::fmt;
+use std::iter::Peekable;
+use std::str::CharIndices;
+
+use common::ParseError;
+use regex::{Regex, RegexBuilder};
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::Entry;
+
+/// The sled rental place's policy, applied by part 1.
+pub const SLED_RENTAL: &str = "count(letter, first, second)";
+/// The Official Toboggan Corporate Policy, applied by part 2.
+pub const TOBOGGAN: &str = "one_of(letter, first, second)";
+
+/// A number in a policy.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Number {
+    Literal(usize),
+    First,
+    Second,
+}
+
+impl Number {
+    fn resolve(self, entry: &Entry) -> usize {
+        match self {
+            Number::Literal(n) => n,
+            Number::First => entry.first,
+            Number::Second => entry.second,
+        }
+    }
+}
+
+impl fmt::Display for Number {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Number::Literal(n) => write!(f, "{}", n),
+            Number::First => write!(f, "first"),
+            Number::Second => write!(f, "second"),
+        }
+    }
+}
+
+/// A letter in a policy: one grapheme.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Letter {
+    Literal(String),
+    /// The entry's own letter.
+    Entry,
+}
+
+impl Letter {
+    fn resolve<'a>(&'a self, entry: &'a Entry) -> &'a str {
+        match self {
+            Letter::Literal(letter) => letter,
+            Letter::Entry => &entry.letter,
+        }
+    }
+}
+
+impl fmt::Display for Letter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Letter::Literal(letter) => write!(f, "'{}'", letter),
+            Letter::Entry => write!(f, "letter"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Class {
+    Lower,
+    Upper,
+    Digit,
+    Alpha,
+    Alnum,
+    Punct,
+}
+
+impl Class {
+    const ALL: [(&'static str, Class); 6] = [
+        ("lower", Class::Lower),
+        ("upper", Class::Upper),
+        ("digit", Class::Digit),
+        ("alpha", Class::Alpha),
+        ("alnum", Class::Alnum),
+        ("punct", Class::Punct),
+    ];
+
+    fn from_name(name: &str) -> Option<Class> {
+        Class::ALL
+            .iter()
+            .find(|(found, _)| *found == name)
+            .map(|(_, class)| *class)
+    }
+
+    fn name(self) -> &'static str {
+        Class::ALL
+            .iter()
+            .find(|(_, class)| *class == self)
+            .map(|(name, _)| *name)
+            .unwrap()
+    }
+
+    fn contains(self, c: char) -> bool {
+        match self {
+            Class::Lower => c.is_lowercase(),
+            Class::Upper => c.is_uppercase(),
+            Class::Digit => c.is_ascii_digit(),
+            Class::Alpha => c.is_alphabetic(),
+            Class::Alnum => c.is_alphanumeric(),
+            Class::Punct => c.is_ascii_punctuation(),
+        }
+    }
+}
+
+/// Whether a password satisfies a policy, and why.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Verdict {
+    pub allowed: bool,
+    pub reason: String,
+}
+
+impl Verdict {
+    fn new(allowed: bool, reason: String) -> Verdict {
+        Verdict { allowed, reason }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Case {
+    Sensitive,
+    Insensitive,
+}
+
+impl Case {
+    fn same(self, a: &str, b: &str) -> bool {
+        match self {
+            Case::Sensitive => a == b,
+            Case::Insensitive => a == b || a.to_lowercase() == b.to_lowercase(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Policy {
+    Count {
+        letter: Letter,
+        min: Number,
+        max: Number,
+    },
+    ExactlyOne {
+        letter: Letter,
+        positions: Vec<Number>,
+    },
+    All {
+        letter: Letter,
+        positions: Vec<Number>,
+    },
+    Matches {
+        re: Regex,
+        /// `re` compiled to ignore case.
+        folded: Regex,
+    },
+    Has {
+        class: Class,
+        min: Number,
+    },
+    And(Box<Policy>, Box<Policy>),
+    Or(Box<Policy>, Box<Policy>),
+    Not(Box<Policy>),
+    IgnoreCase(Box<Policy>),
+}
+
+impl Policy {
+    /// Parses a policy; errors carry the 1-based column of the problem.
+    pub fn parse(text: &str) -> Result<Policy, ParseError> {
+        let mut parser = Parser {
+            tokens: tokenize(text)?,
+            next: 0,
+            end: text.chars().count() + 1,
+        };
+        let policy = parser.or()?;
+        match parser.peek() {
+            None => Ok(policy),
+            Some((token, column)) => Err(unexpected(token, *column)),
+        }
+    }
+
+    /// The policy comparing letters without regard to case.
+    pub fn ignoring_case(self) -> Policy {
+        Policy::IgnoreCase(Box::new(self))
+    }
+
+    /// Whether `entry`'s password satisfies the policy.
+    pub fn allows(&self, entry: &Entry) -> bool {
+        self.explain(entry).allowed
+    }
+
+    /// Whether `entry`'s password satisfies the policy, with the facts that decided it.
+    pub fn explain(&self, entry: &Entry) -> Verdict {
+        self.judge(entry, Case::Sensitive)
+    }
+
+    fn judge(&self, entry: &Entry, case: Case) -> Verdict {
+        let password = &entry.password;
+        let positions = |letter: &Letter, positions: &[Number]| -> (usize, String) {
+            let letter = letter.resolve(entry);
+            let mut holding = 0;
+            let facts: Vec<String> = positions
+                .iter()
+                .map(|position| {
+                    let position = position.resolve(entry);
+                    match letter_at(password, position) {
+                        Some(found) => {
+                            holding += usize::from(case.same(found, letter));
+                            format!("position {} is '{}'", position, found)
+                        }
+                        None => format!("position {} out of range", position),
+                    }
+                })
+                .collect();
+            (holding, facts.join(", "))
+        };
+
+        match self {
+            Policy::Count { letter, min, max } => {
+                let letter = letter.resolve(entry);
+                let count = password
+                    .graphemes(true)
+                    .filter(|found| case.same(found, letter))
+                    .count();
+                let (min, max) = (min.resolve(entry), max.resolve(entry));
+                Verdict::new(
+                    min <= count && count <= max,
+                    format!(
+                        "letter '{}' appears {} time{}, allowed {}-{}",
+                        letter,
+                        count,
+                        if count == 1 { "" } else { "s" },
+                        min,
+                        max
+                    ),
+                )
+            }
+            Policy::ExactlyOne {
+                letter,
+                positions: wanted,
+            } => {
+                let (holding, facts) = positions(letter, wanted);
+                Verdict::new(
+                    holding == 1,
+                    format!(
+                        "{}; '{}' must be at exactly one",
+                        facts,
+                        letter.resolve(entry)
+                    ),
+                )
+            }
+            Policy::All {
+                letter,
+                positions: wanted,
+            } => {
+                let (holding, facts) = positions(letter, wanted);
+                Verdict::new(
+                    holding == wanted.len(),
+                    format!("{}; '{}' must be at all", facts, letter.resolve(entry)),
+                )
+            }
+            Policy::Matches { re, folded } => {
+                let allowed = match case {
+                    Case::Sensitive => re.is_match(password),
+                    Case::Insensitive => folded.is_match(password),
+                };
+                let verb = if allowed { "matches" } else { "does not match" };
+                Verdict::new(allowed, format!("{} /{}/", verb, re.as_str()))
+            }
+            Policy::Has { class, min } => {
+                // A grapheme belongs to the class of its base character.
+                let count = password
+                    .graphemes(true)
+                    .filter(|found| found.chars().next().is_some_and(|c| class.contains(c)))
+                    .count();
+                let min = min.resolve(entry);
+                Verdict::new(
+                    count >= min,
+                    format!(
+                        "{} {} characters, needs at least {}",
+                        count,
+                        class.name(),
+                        min
+                    ),
+                )
+            }
+            Policy::And(a, b) | Policy::Or(a, b) => {
+                let is_and = matches!(self, Policy::And(..));
+                let (a, b) = (a.judge(entry, case), b.judge(entry, case));
+                let allowed = if is_and {
+                    a.allowed && b.allowed
+                } else {
+                    a.allowed || b.allowed
+                };
+                // Give only the reasons that decided the outcome.
+                let deciding: Vec<&str> = [&a, &b]
+                    .iter()
+                    .filter(|side| side.allowed == allowed)
+                    .map(|side| side.reason.as_str())
+                    .collect();
+                Verdict::new(allowed, deciding.join("; "))
+            }
+            Policy::Not(policy) => {
+                let inner = policy.judge(entry, case);
+                Verdict::new(!inner.allowed, inner.reason)
+            }
+            Policy::IgnoreCase(policy) => policy.judge(entry, Case::Insensitive),
+        }
+    }
+
+    /// Number of entries in the database whose passwords satisfy the policy.
+    pub fn count_valid(&self, entries: &[Entry]) -> usize {
+        entries.iter().filter(|entry| self.allows(entry)).count()
+    }
+}
+
+/// The grapheme at 1-based `position`; positions outside the password hold no letter.
+fn letter_at(password: &str, position: usize) -> Option<&str> {
+    password.graphemes(true).nth(position.checked_sub(1)?)
+}
+
+impl fmt::Display for Policy {
+    /// Writes the policy back in the policy language.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let list = |positions: &[Number]| {
+            positions
+                .iter()
+                .map(|position| format!(", {}", position))
+                .collect::<String>()
+        };
+        // An operand needs parentheses when it binds more loosely than the operator around it.
+        let operand = |policy: &Policy, loosest: u8| {
+            let binding = match policy {
+                Policy::Or(..) => 0,
+                Policy::And(..) => 1,
+                _ => 2,
+            };
+            if binding < loosest {
+                format!("({})", policy)
+            } else {
+                policy.to_string()
+            }
+        };
+
+        match self {
+            Policy::Count { letter, min, max } => write!(f, "count({}, {}, {})", letter, min, max),
+            Policy::ExactlyOne { letter, positions } => {
+                write!(f, "one_of({}{})", letter, list(positions))
+            }
+            Policy::All { letter, positions } => write!(f, "all_of({}{})", letter, list(positions)),
+            Policy::Matches { re, .. } => write!(f, "matches({})", quote(re.as_str())),
+            Policy::Has { class, min } => write!(f, "has({}, {})", class.name(), min),
+            Policy::And(a, b) => write!(f, "{} and {}", operand(a, 1), operand(b, 1)),
+            Policy::Or(a, b) => write!(f, "{} or {}", operand(a, 0), operand(b, 0)),
+            Policy::Not(policy) => write!(f, "not {}", operand(policy, 2)),
+            Policy::IgnoreCase(policy) => write!(f, "ignore_case({})", policy),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Word(String),
+    Number(usize),
+    Letter(String),
+    Text(String),
+    Open,
+    Close,
+    Comma,
+}
+
+impl Token {
+    fn describe(&self) -> String {
+        match self {
+            Token::Word(word) => format!("{:?}", word),
+            Token::Number(n) => n.to_string(),
+            Token::Letter(letter) => format!("'{}'", letter),
+            Token::Text(text) => format!("{:?}", text),
+            Token::Open => "\"(\"".to_string(),
+            Token::Close => "\")\"".to_string(),
+            Token::Comma => "\",\"".to_string(),
+        }
+    }
+}
+
+fn unexpected(token: &Token, column: usize) -> ParseError {
+    ParseError::new(format!("unexpected {}", token.describe())).at_column(column)
+}
+
+/// Splits a policy into tokens, each with its 1-based column.
+fn tokenize(text: &str) -> Result<Vec<(Token, usize)>, ParseError> {
+    // Columns count characters, while `char_indices` gives byte offsets.
+    let column = |offset: usize| text[..offset].chars().count() + 1;
+    let mut chars = text.char_indices().peekable();
+    let mut tokens = Vec::new();
+
+    while let Some(&(offset, c)) = chars.peek() {
+        let token = match c {
+            _ if c.is_whitespace() => {
+                chars.next();
+                continue;
+            }
+            '(' | ')' | ',' => {
+                chars.next();
+                match c {
+                    '(' => Token::Open,
+                    ')' => Token::Close,
+                    _ => Token::Comma,
+                }
+            }
+            '\'' => {
+                chars.next();
+                let letter = take_while(&mut chars, |c| c != '\'');
+                match chars.next() {
+                    Some(_) if letter.graphemes(true).count() == 1 => Token::Letter(letter),
+                    _ => {
+                        return Err(ParseError::new("expected a letter such as 'a'")
+                            .at_column(column(offset)))
+                    }
+                }
+            }
+            '"' => {
+                chars.next();
+                Token::Text(quoted(&mut chars).ok_or_else(|| {
+                    ParseError::new("unterminated string").at_column(column(offset))
+                })?)
+            }
+            _ if c.is_ascii_digit() => {
+                let digits = take_while(&mut chars, |c| c.is_ascii_digit());
+                Token::Number(common::parse_number(&digits, column(offset))?)
+            }
+            _ if c.is_alphabetic() || c == '_' => {
+                Token::Word(take_while(&mut chars, |c| c.is_alphanumeric() || c == '_'))
+            }
+            _ => {
+                return Err(ParseError::new(format!("unexpected {:?}", c)).at_column(column(offset)))
+            }
+        };
+        tokens.push((token, column(offset)));
+    }
+
+    Ok(tokens)
+}
+
+fn take_while(chars: &mut Peekable<CharIndices>, keep: impl Fn(char) -> bool) -> String {
+    let mut taken = String::new();
+    while let Some(&(_, c)) = chars.peek() {
+        if !keep(c) {
+            break;
+        }
+        taken.push(c);
+        chars.next();
+    }
+    taken
+}
+
+/// `text` as a string in the policy language, which `quoted` reads back as `text`.
+fn quote(text: &str) -> String {
+    let mut quoted = String::with_capacity(text.len() + 2);
+    quoted.push('"');
+    for c in text.chars() {
+        if c == '"' || c == '\\' {
+            quoted.push('\\');
+        }
+        quoted.push(c);
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// The rest of a string whose opening quote has been read, with `\"` and `\\` unescaped, or `None`
+/// if it never ends.
+fn quoted(chars: &mut Peekable<CharIndices>) -> Option<String> {
+    let mut text = String::new();
+    loop {
+        match chars.next()?.1 {
+            '"' => return Some(text),
+            '\\' => match chars.next()?.1 {
+                escaped @ ('"' | '\\') => text.push(escaped),
+                other => {
+                    text.push('\\');
+                    text.push(other);
+                }
+            },
+            c => text.push(c),
+        }
+    }
+}
+
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    next: usize,
+    /// Column just past the end of the policy, where running out of tokens is reported.
+    end: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&(Token, usize)> {
+        self.tokens.get(self.next)
+    }
+
+    fn advance(&mut self) -> Result<(Token, usize), ParseError> {
+        let token = self
+            .tokens
+            .get(self.next)
+            .cloned()
+            .ok_or_else(|| ParseError::new("unexpected end of policy").at_column(self.end))?;
+        self.next += 1;
+        Ok(token)
+    }
+
+    fn eat_word(&mut self, word: &str) -> bool {
+        match self.peek() {
+            Some((Token::Word(found), _)) if found == word => {
+                self.next += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<(), ParseError> {
+        let (token, column) = self.advance()?;
+        if token == expected {
+            Ok(())
+        } else {
+            Err(ParseError::new(format!(
+                "expected {}, found {}",
+                expected.describe(),
+                token.describe()
+            ))
+            .at_column(column))
+        }
+    }
+
+    fn or(&mut self) -> Result<Policy, ParseError> {
+        let mut policy = self.and()?;
+        while self.eat_word("or") {
+            policy = Policy::Or(Box::new(policy), Box::new(self.and()?));
+        }
+        Ok(policy)
+    }
+
+    fn and(&mut self) -> Result<Policy, ParseError> {
+        let mut policy = self.not()?;
+        while self.eat_word("and") {
+            policy = Policy::And(Box::new(policy), Box::new(self.not()?));
+        }
+        Ok(policy)
+    }
+
+    fn not(&mut self) -> Result<Policy, ParseError> {
+        if self.eat_word("not") {
+            return Ok(Policy::Not(Box::new(self.not()?)));
+        }
+        if let Some((Token::Open, _)) = self.peek() {
+            self.next += 1;
+            let policy = self.or()?;
+            self.expect(Token::Close)?;
+            return Ok(policy);
+        }
+        if self.eat_word("ignore_case") {
+            self.expect(Token::Open)?;
+            let policy = self.or()?;
+            self.expect(Token::Close)?;
+            return Ok(policy.ignoring_case());
+        }
+        self.check()
+    }
+
+    fn check(&mut self) -> Result<Policy, ParseError> {
+        let (token, column) = self.advance()?;
+        let name = match token {
+            Token::Word(name) => name,
+            token => return Err(unexpected(&token, column)),
+        };
+        self.expect(Token::Open)?;
+
+        let policy = match name.as_str() {
+            "count" => {
+                let letter = self.letter()?;
+                self.expect(Token::Comma)?;
+                let min = self.number()?;
+                self.expect(Token::Comma)?;
+                let max = self.number()?;
+                Policy::Count { letter, min, max }
+            }
+            "one_of" | "all_of" => {
+                let letter = self.letter()?;
+                let mut positions = Vec::new();
+                while let Some((Token::Comma, _)) = self.peek() {
+                    self.next += 1;
+                    positions.push(self.number()?);
+                }
+                match name.as_str() {
+                    "one_of" => Policy::ExactlyOne { letter, positions },
+                    _ => Policy::All { letter, positions },
+                }
+            }
+            "matches" => match self.advance()? {
+                (Token::Text(pattern), column) => {
+                    let invalid = |err: regex::Error| {
+                        ParseError::new(format!("invalid regex: {}", err)).at_column(column)
+                    };
+                    Policy::Matches {
+                        re: Regex::new(&pattern).map_err(invalid)?,
+                        folded: RegexBuilder::new(&pattern)
+                            .case_insensitive(true)
+                            .build()
+                            .map_err(invalid)?,
+                    }
+                }
+                (token, column) => return Err(unexpected(&token, column)),
+            },
+            "has" => {
+                let (token, column) = self.advance()?;
+                let class = match &token {
+                    Token::Word(class) => Class::from_name(class),
+                    _ => None,
+                }
+                .ok_or_else(|| {
+                    ParseError::new(format!(
+                        "expected a character class, found {}",
+                        token.describe()
+                    ))
+                    .at_column(column)
+                })?;
+                self.expect(Token::Comma)?;
+                let min = self.number()?;
+                Policy::Has { class, min }
+            }
+            _ => return Err(ParseError::new(format!("unknown check {:?}", name)).at_column(column)),
+        };
+
+        self.expect(Token::Close)?;
+        Ok(policy)
+    }
+
+    fn letter(&mut self) -> Result<Letter, ParseError> {
+        match self.advance()? {
+            (Token::Letter(letter), _) => Ok(Letter::Literal(letter)),
+            (Token::Word(word), _) if word == "letter" => Ok(Letter::Entry),
+            (token, column) => Err(ParseError::new(format!(
+                "expected a letter, found {}",
+                token.describe()
+            ))
+            .at_column(column)),
+        }
+    }
+
+    fn number(&mut self) -> Result<Number, ParseError> {
+        match self.advance()? {
+            (Token::Number(n), _) => Ok(Number::Literal(n)),
+            (Token::Word(word), _) if word == "first" => Ok(Number::First),
+            (Token::Word(word), _) if word == "second" => Ok(Number::Second),
+            (token, column) => Err(ParseError::new(format!(
+                "expected a number, found {}",
+                token.describe()
+            ))
+            .at_column(column)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(password: &str) -> Entry {
+        Entry {
+            first: 1,
+            second: 3,
+            letter: "a".to_string(),
+            password: password.to_string(),
+        }
+    }
+
+    #[test]
+    fn combines_checks() {
+        let policy = Policy::parse(
+            "(count(letter, first, second) or all_of('z', 1, 2)) \
+             and not matches(\"^b\") and has(digit, 1)",
+        )
+        .unwrap();
+        assert!(policy.allows(&entry("a1bcde")));
+        assert!(policy.allows(&entry("zz9")));
+        assert!(!policy.allows(&entry("zz")));
+        assert!(!policy.allows(&entry("ba1")));
+        assert!(!policy.allows(&entry("aaaa1")));
+
+        let policy = Policy::parse("one_of('c', 1, 2, 3) and has(upper, 2)").unwrap();
+        assert!(policy.allows(&entry("cAB")));
+        assert!(!policy.allows(&entry("ccAB")));
+    }
+
+    #[test]
+    fn reports_where_a_policy_is_wrong() {
+        let column = |text| Policy::parse(text).unwrap_err().column;
+        assert_eq!(column("count(letter, 1)"), Some(16));
+        assert_eq!(column("count(letter, 1, 2"), Some(19));
+        assert_eq!(column("size(3)"), Some(1));
+        assert_eq!(column("has(vowel, 1)"), Some(5));
+        assert_eq!(column("matches(\"(\")"), Some(9));
+        assert_eq!(column("has(digit, 1) and"), Some(18));
+        assert_eq!(column("has(digit, 1) has(digit, 2)"), Some(15));
+    }
+
+    #[test]
+    fn ignores_case_when_asked() {
+        let policy = Policy::parse("count(letter, 2, 2) and matches(\"^A\")").unwrap();
+        assert!(!policy.allows(&entry("aAb")));
+        let policy = policy.ignoring_case();
+        assert!(policy.allows(&entry("aAb")));
+        assert_eq!(
+            policy.to_string(),
+            "ignore_case(count(letter, 2, 2) and matches(\"^A\"))"
+        );
+
+        let policy = Policy::parse("ignore_case(one_of('Ä', 1, 2)) and not one_of('Ä', 1, 2)");
+        assert!(policy.unwrap().allows(&entry("äx")));
+    }
+
+    #[test]
+    fn explains_verdicts() {
+        let explain = |policy, password| Policy::parse(policy).unwrap().explain(&entry(password));
+        assert_eq!(
+            explain(SLED_RENTAL, "aaaaa"),
+            Verdict::new(false, "letter 'a' appears 5 times, allowed 1-3".to_string())
+        );
+        assert_eq!(
+            explain("one_of(letter, first, 12)", "abc").reason,
+            "position 1 is 'a', position 12 out of range; 'a' must be at exactly one"
+        );
+        assert_eq!(
+            explain("has(digit, 1) and not matches(\"x\")", "x1"),
+            Verdict::new(false, "matches /x/".to_string())
+        );
+    }
+
+    #[test]
+    fn displays_in_the_policy_language() {
+        let text = "not (has(digit, 1) or all_of('z', 1, first)) and count(letter, 0, 2) \
+                    or matches(\"^\\\\d\")";
+        let policy = Policy::parse(text).unwrap();
+        assert_eq!(
+            policy.to_string(),
+            "not (has(digit, 1) or all_of('z', 1, first)) and count(letter, 0, 2) \
+             or matches(\"^\\\\d\")"
+        );
+        assert_eq!(
+            Policy::parse(&policy.to_string()).unwrap().to_string(),
+            policy.to_string()
+        );
+
+        // Written back, a regex reads as exactly the same pattern, whatever characters it holds.
+        let pattern = |policy: &Policy| match policy {
+            Policy::Matches { re, .. } => re.as_str().to_string(),
+            other => panic!("expected a regex, got {}", other),
+        };
+        let policy = Policy::parse("matches(\"\\d\\\\\\\"\t\u{e9}\")").unwrap();
+        assert_eq!(pattern(&policy), "\\d\\\"\t\u{e9}");
+        assert_eq!(policy.to_string(), "matches(\"\\\\d\\\\\\\"\t\u{e9}\")");
+        assert_eq!(
+            pattern(&Policy::parse(&policy.to_string()).unwrap()),
+            pattern(&policy)
+        );
+    }
+}
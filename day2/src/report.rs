@@ -0,0 +1,146 @@
+//! A line-by-line account of which database entries each policy allows, and why.
+
+use crate::policy::Policy;
+use crate::Entry;
+
+/// The verdict of one policy on one database entry.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Row {
+    /// 1-based line of the entry in the database.
+    pub line: usize,
+    pub policy: String,
+    pub allowed: bool,
+    pub reason: String,
+}
+
+/// Checks every entry against every policy, entry by entry.
+pub fn report(entries: &[Entry], policies: &[Policy]) -> Vec<Row> {
+    let mut rows = Vec::with_capacity(entries.len() * policies.len());
+    for (index, entry) in entries.iter().enumerate() {
+        for policy in policies {
+            let verdict = policy.explain(entry);
+            rows.push(Row {
+                line: index + 1,
+                policy: policy.to_string(),
+                allowed: verdict.allowed,
+                reason: verdict.reason,
+            });
+        }
+    }
+    rows
+}
+
+fn verdict(allowed: bool) -> &'static str {
+    if allowed {
+        "valid"
+    } else {
+        "invalid"
+    }
+}
+
+/// The rows as a table for reading in a terminal.
+pub fn to_text(rows: &[Row]) -> String {
+    // Measured in chars, which is what `{:<width$}` pads to.
+    let width = rows
+        .iter()
+        .map(|row| row.policy.chars().count())
+        .max()
+        .unwrap_or(0);
+    let mut text = format!(
+        "{:>5}  {:<width$}  {:<7}  reason\n",
+        "line",
+        "policy",
+        "verdict",
+        width = width
+    );
+    for row in rows {
+        text += &format!(
+            "{:>5}  {:<width$}  {:<7}  {}\n",
+            row.line,
+            row.policy,
+            verdict(row.allowed),
+            row.reason,
+            width = width
+        );
+    }
+    text
+}
+
+/// The rows as CSV with a header line, quoting fields as RFC 4180 describes.
+pub fn to_csv(rows: &[Row]) -> String {
+    let mut csv = String::from("line,policy,verdict,reason\n");
+    for row in rows {
+        let fields = [
+            row.line.to_string(),
+            row.policy.clone(),
+            verdict(row.allowed).to_string(),
+            row.reason.clone(),
+        ];
+        let fields: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+        csv += &fields.join(",");
+        csv.push('\n');
+    }
+    csv
+}
+
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse, policy, Day2};
+    use common::Solution;
+
+    #[test]
+    fn reports_each_entry_under_each_policy() {
+        let entries = parse(Day2::EXAMPLE).unwrap();
+        let policies = [
+            Policy::parse(policy::SLED_RENTAL).unwrap(),
+            Policy::parse(policy::TOBOGGAN).unwrap(),
+        ];
+        let rows = report(&entries, &policies);
+        assert_eq!(rows.len(), 6);
+        assert_eq!(
+            rows[2],
+            Row {
+                line: 2,
+                policy: "count(letter, first, second)".to_string(),
+                allowed: false,
+                reason: "letter 'b' appears 0 times, allowed 1-3".to_string(),
+            }
+        );
+
+        let csv = to_csv(&rows[2..4]);
+        assert_eq!(
+            csv,
+            "line,policy,verdict,reason\n\
+             2,\"count(letter, first, second)\",invalid,\"letter 'b' appears 0 times, allowed 1-3\"\n\
+             2,\"one_of(letter, first, second)\",invalid,\
+             \"position 1 is 'c', position 3 is 'e'; 'b' must be at exactly one\"\n"
+        );
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert!(to_text(&rows).lines().nth(1).unwrap().contains("valid"));
+    }
+
+    #[test]
+    fn aligns_columns_of_non_ascii_policies() {
+        let row = |policy: &str| Row {
+            line: 1,
+            policy: policy.to_string(),
+            allowed: true,
+            reason: String::new(),
+        };
+        let text = to_text(&[row("count('é', 1, 2)"), row("count('e', 1, 2)")]);
+        let columns: Vec<usize> = text
+            .lines()
+            .map(|line| line.chars().position(|c| c == 'v').unwrap())
+            .collect();
+        assert_eq!(columns, [25, 25, 25]);
+    }
+}
@@ -0,0 +1,117 @@
+use std::error::Error;
+
+use common::{Answer, ParseError, Solution};
+use grid::{Cell, Grid, Wrap};
+use slope::{Route, Slope};
+
+pub mod render;
+pub mod slope;
+
+pub struct Day3;
+
+impl Solution for Day3 {
+    const DAY: u8 = 3;
+    const EXAMPLE: &'static str = include_str!("../fixtures/example.txt");
+    type Input = Grid<Square>;
+
+    fn parse(&self, input: &str) -> Result<Grid<Square>, Box<dyn Error>> {
+        Ok(parse(input)?)
+    }
+
+    fn part1(&self, map: &Grid<Square>) -> Result<Answer, Box<dyn Error>> {
+        Ok(Answer::new(part1(map)))
+    }
+
+    fn part2(&self, map: &Grid<Square>) -> Result<Answer, Box<dyn Error>> {
+        Ok(Answer::new(part2(map)))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Square {
+    Open,
+    Tree,
+}
+
+impl Cell for Square {
+    fn from_char(c: char) -> Option<Square> {
+        match c {
+            '.' => Some(Square::Open),
+            '#' => Some(Square::Tree),
+            _ => None,
+        }
+    }
+
+    fn to_char(&self) -> char {
+        match self {
+            Square::Open => '.',
+            Square::Tree => '#',
+        }
+    }
+}
+
+/// Reads the map, which repeats to the right.
+pub fn parse(input_as_str: &str) -> Result<Grid<Square>, ParseError> {
+    Ok(Grid::parse(input_as_str)?.with_wrap(Wrap::Horizontal))
+}
+
+/// Counts the trees hit going `right` and `down` from the top left until falling off the bottom.
+pub fn trees_in_slope(map: &Grid<Square>, right: isize, down: isize) -> usize {
+    Route::new(Slope::new(right, down)).count(map, &Square::Tree)
+}
+
+pub fn part1(map: &Grid<Square>) -> usize {
+    trees_in_slope(map, 3, 1)
+}
+
+pub fn part2(map: &Grid<Square>) -> usize {
+    let slopes = [(1, 1), (3, 1), (5, 1), (7, 1), (1, 2)];
+
+    let mut tree_mult = 1;
+
+    for slope in &slopes {
+        let trees = trees_in_slope(map, slope.0, slope.1);
+        tree_mult *= trees;
+    }
+
+    tree_mult
+}
+
+/// The slope within `bound` (see [`slope::survey`]) hitting the fewest trees, and how many it hits.
+/// Ties go to the slope that comes first in the survey.
+pub fn fewest_trees(map: &Grid<Square>, bound: usize) -> Option<(Slope, usize)> {
+    slope::survey(map, bound, &Square::Tree)
+        .into_iter()
+        .min_by_key(|(_, trees)| *trees)
+}
+
+/// The slope within `bound` hitting the most trees, and how many it hits. Ties go to the slope
+/// that comes first in the survey.
+pub fn most_trees(map: &Grid<Square>, bound: usize) -> Option<(Slope, usize)> {
+    slope::survey(map, bound, &Square::Tree)
+        .into_iter()
+        .rev()
+        .max_by_key(|(_, trees)| *trees)
+}
+
+common::fixture_tests!(Day3);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_trees_on_a_steep_slope() {
+        let map = parse(Day3::EXAMPLE).unwrap();
+        assert_eq!(trees_in_slope(&map, 1, 2), 2);
+    }
+
+    #[test]
+    fn finds_extreme_slopes() {
+        let map = parse(Day3::EXAMPLE).unwrap();
+        let (slope, trees) = most_trees(&map, 3).unwrap();
+        assert_eq!((slope, trees), (Slope::new(3, 1), 7));
+        assert_eq!(fewest_trees(&map, 3), Some((Slope::new(1, 3), 0)));
+        assert_eq!(fewest_trees(&map, 0), None);
+    }
+}
@@ -1,50 +1,75 @@
-use std::borrow::Cow;
+use std::env;
 use std::error::Error;
 use std::fs;
+use std::path::Path;
+use std::process;
+use std::str::FromStr;
 
-fn main() -> Result<(), Box<dyn Error>> {
-    let input_file = &fs::read("input.txt")?;
-    let input_as_str = String::from_utf8_lossy(&input_file);
-    part1(&input_as_str);
-    part2(&input_as_str);
-    Ok(())
-}
+use day3::render;
+use day3::slope::{Route, Slope};
+use day3::Square;
+use grid::Grid;
 
-fn trees_in_slope(input_as_str: &Cow<str>, right: usize, down: usize) -> usize {
-    let rows = input_as_str.lines();
-    let mut trees = 0;
-
-    let mut x_coord = 0;
-    let mut y_coord = 0;
+/// `day3 search <map> [<bound>]` finds the slopes within `bound` (default 3) hitting the fewest
+/// and the most trees. `day3 render <map> <slope> [<out>]` draws a route onto the map, as text on
+/// stdout or into `out`, which is a PPM image if it ends in `.ppm`. Anything else is the usual day
+/// binary.
+fn main() -> Result<(), Box<dyn Error>> {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let result = match args.first().map(String::as_str) {
+        Some("search") => search_main(&args[1..]),
+        Some("render") => render_main(&args[1..]),
+        _ => return common::run_main(&day3::Day3),
+    };
 
-    for row in rows.skip(1) {
-        y_coord += 1;
-        if y_coord % down == 0 {
-            x_coord = (x_coord + right) % row.len();
-            if row.chars().nth(x_coord).unwrap() == '#' {
-                trees += 1;
-            }
-        }
+    if let Err(err) = result {
+        eprintln!("error: {}", err);
+        process::exit(1);
     }
+    Ok(())
+}
 
-    trees
+fn read_map(path: &str) -> Result<Grid<Square>, String> {
+    let text = fs::read_to_string(path).map_err(|err| format!("{}: {}", path, err))?;
+    day3::parse(&text).map_err(|err| format!("{}:{}", path, err))
 }
 
-fn part1(input_as_str: &Cow<str>) {
-    let trees = trees_in_slope(input_as_str, 3, 1);
+fn search_main(args: &[String]) -> Result<(), Box<dyn Error>> {
+    let path = args.first().ok_or("usage: day3 search <map> [<bound>]")?;
+    let bound = match args.get(1) {
+        Some(bound) => bound.parse()?,
+        None => 3,
+    };
 
-    println!("Part1: trees: {}", trees);
+    let map = read_map(path)?;
+    let found = day3::fewest_trees(&map, bound).zip(day3::most_trees(&map, bound));
+    let ((fewest, few), (most, many)) = found.ok_or("the bound must be at least 1")?;
+    println!("fewest: {} trees at slope {}", few, fewest);
+    println!("most: {} trees at slope {}", many, most);
+    Ok(())
 }
 
-fn part2(input_as_str: &Cow<str>) {
-    let slopes = [(1, 1), (3, 1), (5, 1), (7, 1), (1, 2)];
+fn render_main(args: &[String]) -> Result<(), Box<dyn Error>> {
+    let usage = "usage: day3 render <map> <slope> [<out>]";
+    let (path, slope) = match args {
+        [path, slope] | [path, slope, _] => (path, slope),
+        _ => return Err(usage.into()),
+    };
 
-    let mut tree_mult = 1;
+    let map = read_map(path)?;
+    let slope = Slope::from_str(slope).map_err(|err| format!("<slope>:{}", err.at_line(1)))?;
+    let marked = render::mark(&map, &Route::new(slope));
 
-    for slope in &slopes {
-        let trees = trees_in_slope(input_as_str, slope.0, slope.1);
-        tree_mult *= trees;
+    match args.get(2).map(Path::new) {
+        None => print!("{}", render::to_text(&marked)),
+        Some(out) => {
+            let contents = if out.extension().is_some_and(|ext| ext == "ppm") {
+                render::to_ppm(&marked, 4)
+            } else {
+                render::to_text(&marked).into_bytes()
+            };
+            fs::write(out, contents).map_err(|err| format!("{}: {}", out.display(), err))?;
+        }
     }
-
-    println!("Part2: trees: {}", tree_mult);
+    Ok(())
 }
@@ -0,0 +1,108 @@
+//! Drawing a route onto its map: `O` where the route hits a tree and `X` where it lands on an
+//! open square, as in the puzzle text.
+
+use grid::Grid;
+
+use crate::slope::Route;
+use crate::Square;
+
+/// What a square looks like once a route is drawn over the map.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Mark {
+    Open,
+    Tree,
+    /// A tree the route hits.
+    Hit,
+    /// An open square the route lands on.
+    Clear,
+}
+
+impl Mark {
+    fn to_char(self) -> char {
+        match self {
+            Mark::Open => '.',
+            Mark::Tree => '#',
+            Mark::Hit => 'O',
+            Mark::Clear => 'X',
+        }
+    }
+
+    fn rgb(self) -> [u8; 3] {
+        match self {
+            Mark::Open => [240, 240, 240],
+            Mark::Tree => [34, 139, 34],
+            Mark::Hit => [220, 20, 60],
+            Mark::Clear => [30, 144, 255],
+        }
+    }
+}
+
+/// The map with the squares `route` lands on marked.
+pub fn mark(map: &Grid<Square>, route: &Route) -> Grid<Mark> {
+    let mut marked = Grid::new(map.width(), map.height(), Mark::Open);
+    for pos in map.positions() {
+        if map[pos] == Square::Tree {
+            marked[pos] = Mark::Tree;
+        }
+    }
+    for pos in route.positions(map) {
+        marked[pos] = match map[pos] {
+            Square::Tree => Mark::Hit,
+            Square::Open => Mark::Clear,
+        };
+    }
+    marked
+}
+
+pub fn to_text(marked: &Grid<Mark>) -> String {
+    marked.render_with(|mark| mark.to_char())
+}
+
+/// A binary PPM image of the marked map, drawing each square as `scale` by `scale` pixels.
+pub fn to_ppm(marked: &Grid<Mark>, scale: usize) -> Vec<u8> {
+    let scale = scale.max(1);
+    let (width, height) = (marked.width() * scale, marked.height() * scale);
+    let mut image = format!("P6\n{} {}\n255\n", width, height).into_bytes();
+    image.reserve(width * height * 3);
+
+    for row in marked.rows() {
+        let mut line = Vec::with_capacity(width * 3);
+        for mark in row {
+            for _ in 0..scale {
+                line.extend_from_slice(&mark.rgb());
+            }
+        }
+        for _ in 0..scale {
+            image.extend_from_slice(&line);
+        }
+    }
+    image
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::slope::Slope;
+    use crate::{parse, Day3};
+    use common::Solution;
+
+    #[test]
+    fn draws_the_route_as_in_the_puzzle() {
+        let map = parse(Day3::EXAMPLE).unwrap();
+        let marked = mark(&map, &Route::new(Slope::new(3, 1)));
+        let text = to_text(&marked);
+        assert_eq!(text.lines().nth(1), Some("#..X#...#.."));
+        assert_eq!(text.lines().nth(2), Some(".#....O..#."));
+        assert_eq!(text.matches('O').count(), 7);
+        assert_eq!(text.matches('X').count(), 3);
+
+        let ppm = to_ppm(&marked, 2);
+        let header = b"P6\n22 22\n255\n";
+        assert!(ppm.starts_with(header));
+        assert_eq!(ppm.len(), header.len() + 22 * 22 * 3);
+        // The second row of squares starts at pixel row 2; its fourth square is open and
+        // on the route.
+        let pixel = header.len() + (2 * 22 + 3 * 2) * 3;
+        assert_eq!(ppm[pixel..pixel + 3], Mark::Clear.rgb());
+    }
+}
@@ -0,0 +1,173 @@
+//! Toboggan routes across a map: a slope, where the route starts, and what happens at the edges.
+
+use std::fmt;
+use std::str::FromStr;
+
+use common::{parse_number, ParseError};
+use grid::{Grid, Pos, Wrap};
+
+/// How far the toboggan moves each step; negative values go left or up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Slope {
+    pub right: isize,
+    pub down: isize,
+}
+
+impl Slope {
+    /// The slope stepping exactly `right` and `down` at a time, as the puzzle's slopes do.
+    pub fn new(right: isize, down: isize) -> Slope {
+        Slope { right, down }
+    }
+
+    /// The slope of `right` columns per `down` rows, stepping to every square on the line that is
+    /// at a whole column: `rational(2, 4)` steps one right and two down.
+    pub fn rational(right: isize, down: isize) -> Slope {
+        let divisor = gcd(right.unsigned_abs(), down.unsigned_abs()).max(1) as isize;
+        Slope::new(right / divisor, down / divisor)
+    }
+}
+
+fn gcd(a: usize, b: usize) -> usize {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+impl fmt::Display for Slope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.right, self.down)
+    }
+}
+
+impl FromStr for Slope {
+    type Err = ParseError;
+
+    /// Reads `right/down` as a rational slope, or a bare `right` as one row down.
+    fn from_str(text: &str) -> Result<Slope, ParseError> {
+        match text.split_once('/') {
+            Some((right, down)) => Ok(Slope::rational(
+                parse_number(right, 1)?,
+                parse_number(down, right.chars().count() + 2)?,
+            )),
+            None => Ok(Slope::new(parse_number(text, 1)?, 1)),
+        }
+    }
+}
+
+/// A route down the map. By default it starts at the top left and, like the puzzle's map, repeats
+/// to the left and right, ending when it leaves the top or bottom. A route on a map that wraps in
+/// every direction ends when it comes back to its start.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Route {
+    pub slope: Slope,
+    pub start: Pos,
+    pub wrap: Wrap,
+}
+
+impl Route {
+    pub fn new(slope: Slope) -> Route {
+        Route {
+            slope,
+            start: Pos::new(0, 0),
+            wrap: Wrap::Horizontal,
+        }
+    }
+
+    /// Starts the route at `start`, which may be off the map if the route wraps onto it.
+    pub fn starting_at(mut self, start: Pos) -> Route {
+        self.start = start;
+        self
+    }
+
+    /// Sets what happens at the edges; `Wrap::Clamped` stops the route at any edge.
+    pub fn with_wrap(mut self, wrap: Wrap) -> Route {
+        self.wrap = wrap;
+        self
+    }
+
+    /// The squares the route lands on, not including its start.
+    pub fn positions<'a, T>(&self, map: &'a Grid<T>) -> impl Iterator<Item = Pos> + 'a {
+        map.ray_wrapping(self.start, (self.slope.right, self.slope.down), self.wrap)
+    }
+
+    /// How many of the squares the route lands on hold `cell`.
+    pub fn count<T: PartialEq>(&self, map: &Grid<T>, cell: &T) -> usize {
+        self.positions(map).filter(|pos| map[*pos] == *cell).count()
+    }
+}
+
+/// Counts the `cell`s on the route from the top left for every slope going at most `bound`
+/// squares left or right and between 1 and `bound` squares down, ordered by `down` then `right`.
+pub fn survey<T: PartialEq>(map: &Grid<T>, bound: usize, cell: &T) -> Vec<(Slope, usize)> {
+    let bound = bound as isize;
+    let mut counts = Vec::new();
+    for down in 1..=bound {
+        for right in -bound..=bound {
+            let slope = Slope::new(right, down);
+            counts.push((slope, Route::new(slope).count(map, cell)));
+        }
+    }
+    counts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse, Day3, Square};
+    use common::Solution;
+
+    #[test]
+    fn follows_any_slope_from_any_start() {
+        let map = parse(Day3::EXAMPLE).unwrap();
+        let trees = |route: Route| route.count(&map, &Square::Tree);
+
+        assert_eq!(trees(Route::new(Slope::new(3, 1))), 7);
+        // Going back up from where the 3/1 route ends retraces it, counting the open top left
+        // square instead of the tree the route ended on.
+        let up = Route::new(Slope::new(-3, -1)).starting_at(Pos::new(8, 10));
+        assert_eq!(trees(up), 6);
+        assert_eq!(
+            trees(Route::new(Slope::new(3, 1)).with_wrap(Wrap::Clamped)),
+            1
+        );
+        assert_eq!(Route::new(Slope::new(1, 0)).positions(&map).count(), 10);
+        assert_eq!(
+            Route::new(Slope::new(1, 1))
+                .with_wrap(Wrap::Toroidal)
+                .positions(&map)
+                .count(),
+            10
+        );
+        assert_eq!(trees(Route::new(Slope::new(1, 1))), 2);
+
+        // Starts off the map wrap onto it, so routes that wrap still end where they began.
+        let far = Route::new(Slope::new(3, 1)).starting_at(Pos::new(50, 50));
+        assert_eq!(far.with_wrap(Wrap::Toroidal).positions(&map).count(), 10);
+        assert_eq!(far.positions(&map).count(), 0);
+        let sideways = Route::new(Slope::new(1, 0)).starting_at(Pos::new(13, 2));
+        assert_eq!(sideways.positions(&map).count(), 10);
+        assert_eq!(sideways.with_wrap(Wrap::Clamped).positions(&map).count(), 0);
+        assert_eq!(Route::new(Slope::new(1, 1)).count(&map, &Square::Open), 8);
+    }
+
+    #[test]
+    fn surveys_every_slope_within_the_bound() {
+        let map = parse(Day3::EXAMPLE).unwrap();
+        let counts = survey(&map, 3, &Square::Tree);
+        assert_eq!(counts.len(), 3 * 7);
+        assert_eq!(counts[0], (Slope::new(-3, 1), 3));
+        assert!(counts.contains(&(Slope::new(3, 1), 7)));
+        assert!(counts.contains(&(Slope::new(1, 2), 2)));
+    }
+
+    #[test]
+    fn reads_rational_slopes() {
+        assert_eq!("3".parse(), Ok(Slope::new(3, 1)));
+        assert_eq!("-2/4".parse(), Ok(Slope::new(-1, 2)));
+        assert_eq!("1/2".parse::<Slope>().unwrap().to_string(), "1/2");
+        assert_eq!("1/x".parse::<Slope>().unwrap_err().column, Some(3));
+        assert_eq!(Slope::rational(0, -5), Slope::new(0, -1));
+    }
+}
@@ -0,0 +1,128 @@
+use regex::Regex;
+use std::collections::HashMap;
+use std::error::Error;
+
+use common::{Answer, ParseError, Solution};
+use schema::Schema;
+
+pub mod report;
+pub mod schema;
+
+pub struct Day4;
+
+impl Solution for Day4 {
+    const DAY: u8 = 4;
+    const EXAMPLE: &'static str = include_str!("../fixtures/example.txt");
+    type Input = Vec<Passport>;
+
+    fn parse(&self, input: &str) -> Result<Vec<Passport>, Box<dyn Error>> {
+        Ok(parse(input)?)
+    }
+
+    fn part1(&self, passports: &Vec<Passport>) -> Result<Answer, Box<dyn Error>> {
+        Ok(Answer::new(part1(passports)))
+    }
+
+    fn part2(&self, passports: &Vec<Passport>) -> Result<Answer, Box<dyn Error>> {
+        Ok(Answer::new(part2(passports)))
+    }
+}
+
+/// The `key:value` fields of one passport.
+pub type Passport = HashMap<String, String>;
+
+/// A passport together with the line of the batch file it starts on.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Record {
+    pub line: usize,
+    pub passport: Passport,
+}
+
+/// Splits the batch file into passports, which are separated by blank lines.
+pub fn parse(input_as_str: &str) -> Result<Vec<Passport>, ParseError> {
+    let records = parse_records(input_as_str)?;
+    Ok(records.into_iter().map(|record| record.passport).collect())
+}
+
+/// Like `parse`, but keeps the line each passport starts on.
+pub fn parse_records(input_as_str: &str) -> Result<Vec<Record>, ParseError> {
+    let token_re = Regex::new(r"\S+").unwrap();
+    let prop_re = Regex::new(r"^([a-zA-Z]{3}):(\S+)$").unwrap();
+
+    let mut records = Vec::new();
+    let mut current: Option<Record> = None;
+
+    for (index, line) in input_as_str.lines().enumerate() {
+        if line.trim().is_empty() {
+            records.extend(current.take());
+            continue;
+        }
+
+        let record = current.get_or_insert_with(|| Record {
+            line: index + 1,
+            passport: Passport::new(),
+        });
+        for token in token_re.find_iter(line) {
+            let capture = prop_re.captures(token.as_str()).ok_or_else(|| {
+                ParseError::new(format!(
+                    "expected \"key:value\", found {:?}",
+                    token.as_str()
+                ))
+                .at_line(index + 1)
+                .at_column(line[..token.start()].chars().count() + 1)
+            })?;
+            let prop = &capture[1];
+            let val = &capture[2];
+            record.passport.insert(prop.to_string(), val.to_string());
+        }
+    }
+
+    records.extend(current);
+    Ok(records)
+}
+
+/// Whether the passport has every field the puzzle requires.
+pub fn valid_passport(passport: &Passport) -> bool {
+    Schema::builtin().has_required(passport)
+}
+
+pub fn part1(passports: &[Passport]) -> usize {
+    passports.iter().filter(|p| valid_passport(p)).count()
+}
+
+/// Whether the passport has every required field and their values follow the puzzle's rules.
+pub fn strictly_valid_passport(passport: &Passport) -> bool {
+    Schema::builtin().is_valid(passport)
+}
+
+pub fn part2(passports: &[Passport]) -> usize {
+    passports
+        .iter()
+        .filter(|p| strictly_valid_passport(p))
+        .count()
+}
+
+common::fixture_tests!(Day4);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn separates_passports_at_blank_lines() {
+        assert_eq!(parse(Day4::EXAMPLE).unwrap().len(), 4);
+    }
+
+    #[test]
+    fn rejects_fields_without_values() {
+        let err = parse("ecl:gry pid:860033327\n\nhcl:#ae17e1  iyr2013\n").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "3:14: expected \"key:value\", found \"iyr2013\""
+        );
+
+        let mut overflowing = parse(Day4::EXAMPLE).unwrap().remove(0);
+        overflowing.insert("byr".to_string(), "99999999999".to_string());
+        assert!(!strictly_valid_passport(&overflowing));
+    }
+}
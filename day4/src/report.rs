@@ -0,0 +1,124 @@
+//! A record-by-record account of what is wrong with each passport in a batch, and which failures
+//! are the most common.
+
+use std::collections::HashMap;
+
+use crate::schema::{Problem, Schema};
+use crate::Record;
+
+/// The problems with one passport.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Row {
+    /// 1-based line the passport starts on in the batch file.
+    pub line: usize,
+    /// Empty if the passport is valid.
+    pub problems: Vec<Problem>,
+}
+
+/// Checks every passport against the schema.
+pub fn report(records: &[Record], schema: &Schema) -> Vec<Row> {
+    records
+        .iter()
+        .map(|record| Row {
+            line: record.line,
+            problems: schema.diagnose(&record.passport),
+        })
+        .collect()
+}
+
+/// A problem without the value that caused it, so that e.g. every height out of range in inches
+/// counts as the same failure.
+fn failure(problem: &Problem) -> String {
+    match problem {
+        Problem::Missing(_) => problem.to_string(),
+        Problem::Invalid { field, reason, .. } => format!("{} {}", field, reason),
+    }
+}
+
+/// How often each failure occurs across the rows, most common first, ties in alphabetical order.
+pub fn failure_counts(rows: &[Row]) -> Vec<(String, usize)> {
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for problem in rows.iter().flat_map(|row| &row.problems) {
+        *counts.entry(failure(problem)).or_insert(0) += 1;
+    }
+    let mut counts: Vec<(String, usize)> = counts.into_iter().collect();
+    counts.sort_by(|(a, m), (b, n)| n.cmp(m).then_with(|| a.cmp(b)));
+    counts
+}
+
+/// One line per passport listing its problems, then a summary of the most common failures.
+pub fn to_text(rows: &[Row]) -> String {
+    let mut text = String::new();
+    for row in rows {
+        if row.problems.is_empty() {
+            text += &format!("line {}: valid\n", row.line);
+        } else {
+            let problems: Vec<String> = row.problems.iter().map(Problem::to_string).collect();
+            text += &format!("line {}: {}\n", row.line, problems.join("; "));
+        }
+    }
+
+    let valid = rows.iter().filter(|row| row.problems.is_empty()).count();
+    text += &format!("\n{} of {} passports valid\n", valid, rows.len());
+    let counts = failure_counts(rows);
+    if !counts.is_empty() {
+        text += "most common failures:\n";
+        for (failure, count) in counts {
+            text += &format!("{:>5}  {}\n", count, failure);
+        }
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_records;
+
+    #[test]
+    fn reports_each_problem_and_counts_failures() {
+        let records = parse_records(
+            "byr:1937 iyr:2017 eyr:2020 hgt:183cm
+hcl:#fffffd ecl:gry pid:860033327
+
+hgt:190in byr:1950 iyr:2015
+hcl:#123abc ecl:brn pid:012345678
+
+hgt:77in byr:2010 iyr:2011 eyr:2025
+hcl:#123abc ecl:wat pid:012345678
+",
+        )
+        .unwrap();
+        let rows = report(&records, Schema::builtin());
+        assert_eq!(
+            rows.iter().map(|row| row.line).collect::<Vec<_>>(),
+            [1, 4, 7]
+        );
+        assert!(rows[0].problems.is_empty());
+        assert_eq!(
+            rows[1].problems,
+            [
+                Problem::Missing("eyr".to_string()),
+                Problem::Invalid {
+                    field: "hgt".to_string(),
+                    value: "190in".to_string(),
+                    reason: "out of range 59–76in".to_string(),
+                },
+            ]
+        );
+
+        let counts = failure_counts(&rows);
+        assert_eq!(counts[0], ("hgt out of range 59–76in".to_string(), 2));
+        assert_eq!(counts.len(), 4);
+
+        let text = to_text(&rows);
+        let mut lines = text.lines();
+        assert_eq!(lines.next(), Some("line 1: valid"));
+        assert_eq!(
+            lines.next(),
+            Some("line 4: eyr is missing; hgt=190in out of range 59–76in")
+        );
+        assert!(text.contains("\n1 of 3 passports valid\n"));
+        assert!(text.contains("    2  hgt out of range 59–76in\n"));
+    }
+}
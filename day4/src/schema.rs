@@ -0,0 +1,378 @@
+//! Passport schemas: the fields a passport may have, which of them are required, and the rule each
+//! value must follow. Schemas are JSON documents read at runtime and compiled once, so regexes
+//! are built when the schema is loaded rather than for every passport. `schema.json` at the root
+//! of this crate is the schema from the puzzle and documents the format.
+
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::sync::OnceLock;
+
+use regex::Regex;
+use serde::Deserialize;
+
+use crate::Passport;
+
+/// Inclusive bounds on a number.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct Bounds {
+    pub min: i64,
+    pub max: i64,
+}
+
+impl Bounds {
+    fn contains(self, n: i64) -> bool {
+        self.min <= n && n <= self.max
+    }
+}
+
+impl fmt::Display for Bounds {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}–{}", self.min, self.max)
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SchemaConfig {
+    fields: Vec<FieldConfig>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FieldConfig {
+    name: String,
+    #[serde(default)]
+    required: bool,
+    #[serde(default)]
+    rule: Option<RuleConfig>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+enum RuleConfig {
+    Range(Bounds),
+    OneOf(Vec<String>),
+    Regex(String),
+    Units(BTreeMap<String, Bounds>),
+}
+
+/// What a field's value must look like.
+#[derive(Debug, Clone)]
+pub enum Rule {
+    /// A whole number within the bounds.
+    Range(Bounds),
+    /// One of the listed words.
+    OneOf(Vec<String>),
+    /// The whole value matches the regex; `pattern` is the regex as written in the schema.
+    Regex { pattern: String, re: Regex },
+    /// A whole number directly followed by one of the units, within that unit's bounds.
+    Units(Vec<(String, Bounds)>),
+}
+
+impl Rule {
+    fn compile(config: RuleConfig) -> Result<Rule, String> {
+        let check_bounds = |bounds: &Bounds| {
+            if bounds.min > bounds.max {
+                Err(format!("range {} is empty", bounds))
+            } else {
+                Ok(())
+            }
+        };
+
+        match config {
+            RuleConfig::Range(bounds) => {
+                check_bounds(&bounds)?;
+                Ok(Rule::Range(bounds))
+            }
+            RuleConfig::OneOf(words) => Ok(Rule::OneOf(words)),
+            RuleConfig::Regex(pattern) => {
+                let re = Regex::new(&format!("^(?:{})$", pattern))
+                    .map_err(|err| format!("invalid regex: {}", err))?;
+                Ok(Rule::Regex { pattern, re })
+            }
+            RuleConfig::Units(units) => {
+                if units.is_empty() {
+                    return Err("no units given".to_string());
+                }
+                for bounds in units.values() {
+                    check_bounds(bounds)?;
+                }
+                Ok(Rule::Units(units.into_iter().collect()))
+            }
+        }
+    }
+
+    /// Checks `value`, describing what is wrong with it if it breaks the rule.
+    pub fn check(&self, value: &str) -> Result<(), String> {
+        let number = |digits: &str| -> Option<i64> {
+            if !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit()) {
+                digits.parse().ok()
+            } else {
+                None
+            }
+        };
+
+        match self {
+            Rule::Range(bounds) => match number(value) {
+                Some(n) if bounds.contains(n) => Ok(()),
+                Some(_) => Err(format!("out of range {}", bounds)),
+                None => Err("not a number".to_string()),
+            },
+            Rule::OneOf(words) => {
+                if words.iter().any(|word| word == value) {
+                    Ok(())
+                } else {
+                    Err(format!("not one of {}", words.join(", ")))
+                }
+            }
+            Rule::Regex { pattern, re } => {
+                if re.is_match(value) {
+                    Ok(())
+                } else {
+                    Err(format!("does not match {}", pattern))
+                }
+            }
+            Rule::Units(units) => {
+                let split = value
+                    .find(|c: char| !c.is_ascii_digit())
+                    .unwrap_or(value.len());
+                let (digits, unit) = value.split_at(split);
+                let bounds = units.iter().find(|(name, _)| name == unit);
+                match (number(digits), bounds) {
+                    (Some(n), Some((_, bounds))) if bounds.contains(n) => Ok(()),
+                    (Some(_), Some((unit, bounds))) => {
+                        Err(format!("out of range {}{}", bounds, unit))
+                    }
+                    _ => {
+                        let names: Vec<&str> =
+                            units.iter().map(|(name, _)| name.as_str()).collect();
+                        Err(format!(
+                            "expected a number followed by {}",
+                            names.join(" or ")
+                        ))
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// One field of a schema.
+#[derive(Debug, Clone)]
+pub struct Field {
+    pub name: String,
+    pub required: bool,
+    /// The rule the value must follow; any value will do if there is none.
+    pub rule: Option<Rule>,
+}
+
+/// Something wrong with a passport, as found by `Schema::diagnose`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Problem {
+    /// A required field is missing.
+    Missing(String),
+    /// A field's value breaks its rule, for the reason given.
+    Invalid {
+        field: String,
+        value: String,
+        reason: String,
+    },
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Problem::Missing(field) => write!(f, "{} is missing", field),
+            Problem::Invalid {
+                field,
+                value,
+                reason,
+            } => write!(f, "{}={} {}", field, value, reason),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Schema {
+    pub fields: Vec<Field>,
+}
+
+impl Schema {
+    /// Compiles a schema from its JSON text.
+    pub fn parse(json: &str) -> Result<Schema, Box<dyn Error>> {
+        let config: SchemaConfig = serde_json::from_str(json)?;
+        let mut fields: Vec<Field> = Vec::with_capacity(config.fields.len());
+
+        for field in config.fields {
+            if fields.iter().any(|seen| seen.name == field.name) {
+                return Err(format!("field {:?} is declared twice", field.name).into());
+            }
+            let name = field.name;
+            let rule = match field.rule {
+                Some(rule) => {
+                    Some(Rule::compile(rule).map_err(|err| format!("field {:?}: {}", name, err))?)
+                }
+                None => None,
+            };
+            fields.push(Field {
+                name,
+                required: field.required,
+                rule,
+            });
+        }
+
+        Ok(Schema { fields })
+    }
+
+    pub fn load(path: &Path) -> Result<Schema, Box<dyn Error>> {
+        let json =
+            fs::read_to_string(path).map_err(|err| format!("{}: {}", path.display(), err))?;
+        Schema::parse(&json).map_err(|err| format!("{}: {}", path.display(), err).into())
+    }
+
+    /// The schema from the puzzle, compiled on first use.
+    pub fn builtin() -> &'static Schema {
+        static BUILTIN: OnceLock<Schema> = OnceLock::new();
+        BUILTIN.get_or_init(|| {
+            Schema::parse(include_str!("../schema.json")).expect("the built-in schema is valid")
+        })
+    }
+
+    /// Whether the passport has every required field.
+    pub fn has_required(&self, passport: &Passport) -> bool {
+        self.fields
+            .iter()
+            .filter(|field| field.required)
+            .all(|field| passport.contains_key(&field.name))
+    }
+
+    /// Whether the passport has every required field, and every field it has follows its rule.
+    /// Fields the schema does not mention are ignored.
+    pub fn is_valid(&self, passport: &Passport) -> bool {
+        self.has_required(passport)
+            && self
+                .fields
+                .iter()
+                .all(|field| match (passport.get(&field.name), &field.rule) {
+                    (Some(value), Some(rule)) => rule.check(value).is_ok(),
+                    _ => true,
+                })
+    }
+
+    /// Everything wrong with the passport, in the order the schema lists its fields. The
+    /// passport is valid exactly when this is empty.
+    pub fn diagnose(&self, passport: &Passport) -> Vec<Problem> {
+        let mut problems = Vec::new();
+        for field in &self.fields {
+            match (passport.get(&field.name), &field.rule) {
+                (None, _) if field.required => problems.push(Problem::Missing(field.name.clone())),
+                (Some(value), Some(rule)) => {
+                    if let Err(reason) = rule.check(value) {
+                        problems.push(Problem::Invalid {
+                            field: field.name.clone(),
+                            value: value.clone(),
+                            reason,
+                        });
+                    }
+                }
+                _ => {}
+            }
+        }
+        problems
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checks_values_against_rules() {
+        let schema = Schema::builtin();
+        let rule = |name: &str| {
+            let field = schema.fields.iter().find(|field| field.name == name);
+            field.unwrap().rule.as_ref().unwrap()
+        };
+
+        assert_eq!(rule("byr").check("2002"), Ok(()));
+        assert_eq!(
+            rule("byr").check("2003"),
+            Err("out of range 1920–2002".to_string())
+        );
+        assert_eq!(rule("byr").check("+2000"), Err("not a number".to_string()));
+        assert_eq!(rule("hgt").check("60in"), Ok(()));
+        assert_eq!(
+            rule("hgt").check("190in"),
+            Err("out of range 59–76in".to_string())
+        );
+        assert_eq!(
+            rule("hgt").check("190"),
+            Err("expected a number followed by cm or in".to_string())
+        );
+        assert_eq!(
+            rule("hcl").check("#123abcd"),
+            Err("does not match #[0-9a-f]{6}".to_string())
+        );
+        assert!(rule("ecl")
+            .check("wat")
+            .unwrap_err()
+            .starts_with("not one of amb, "));
+        assert!(rule("pid").check("0123456789").is_err());
+    }
+
+    #[test]
+    fn loads_custom_schemas() {
+        let schema = Schema::parse(
+            r#"{ "fields": [
+                { "name": "pin", "required": true, "rule": { "regex": "[0-9]{4}" } },
+                { "name": "age", "rule": { "range": { "min": 18, "max": 120 } } }
+            ] }"#,
+        )
+        .unwrap();
+        let passport = |fields: &[(&str, &str)]| -> Passport {
+            fields
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect()
+        };
+        assert!(schema.is_valid(&passport(&[("pin", "1234")])));
+        assert!(!schema.is_valid(&passport(&[("pin", "1234"), ("age", "12")])));
+        assert!(!schema.has_required(&passport(&[("age", "30")])));
+        assert_eq!(
+            schema.diagnose(&passport(&[("age", "12")])),
+            vec![
+                Problem::Missing("pin".to_string()),
+                Problem::Invalid {
+                    field: "age".to_string(),
+                    value: "12".to_string(),
+                    reason: "out of range 18–120".to_string(),
+                },
+            ]
+        );
+
+        let error = |json| Schema::parse(json).unwrap_err().to_string();
+        assert_eq!(
+            error(r#"{ "fields": [{ "name": "x", "rule": { "regex": "(" } }] }"#)
+                .lines()
+                .next(),
+            Some("field \"x\": invalid regex: regex parse error:")
+        );
+        assert_eq!(
+            error(
+                r#"{ "fields": [{ "name": "x", "rule": { "range": { "min": 2, "max": 1 } } }] }"#
+            ),
+            "field \"x\": range 2–1 is empty"
+        );
+        assert_eq!(
+            error(r#"{ "fields": [{ "name": "x" }, { "name": "x" }] }"#),
+            "field \"x\" is declared twice"
+        );
+        assert!(
+            error(r#"{ "fields": [{ "name": "x", "rule": { "even": true } }] }"#)
+                .contains("unknown variant")
+        );
+    }
+}
@@ -0,0 +1,114 @@
+use itertools::sorted;
+use std::error::Error;
+
+use common::{parse_lines, Answer, ParseError, Solution};
+
+pub struct Day5;
+
+impl Solution for Day5 {
+    const DAY: u8 = 5;
+    const EXAMPLE: &'static str = include_str!("../fixtures/example.txt");
+    type Input = Vec<i16>;
+
+    fn parse(&self, input: &str) -> Result<Vec<i16>, Box<dyn Error>> {
+        Ok(parse(input)?)
+    }
+
+    fn part1(&self, input: &Vec<i16>) -> Result<Answer, Box<dyn Error>> {
+        Ok(Answer::new(part1(input).ok_or("no boarding passes")?))
+    }
+
+    fn part2(&self, input: &Vec<i16>) -> Result<Answer, Box<dyn Error>> {
+        Ok(Answer::new(part2(input)))
+    }
+}
+
+/// Decodes a boarding pass such as `FBFBBFFRLR` into its seat id.
+pub fn parse_coord_id(input: &str) -> i16 {
+    let mut id: i16 = 0;
+
+    for i in input.chars().take(10) {
+        id <<= 1;
+        if i == 'B' || i == 'R' {
+            id += 1;
+        }
+    }
+
+    id
+}
+
+/// Checks that `line` is seven `F`/`B` row letters followed by three `L`/`R` column letters, then
+/// decodes it.
+pub fn parse_boarding_pass(line: &str) -> Result<i16, ParseError> {
+    for (index, c) in line.chars().enumerate() {
+        let expected = if index < 7 { ['F', 'B'] } else { ['L', 'R'] };
+        if index >= 10 || !expected.contains(&c) {
+            return Err(
+                ParseError::new(format!("unexpected {:?} in boarding pass", c))
+                    .at_column(index + 1),
+            );
+        }
+    }
+
+    if line.chars().count() < 10 {
+        return Err(ParseError::new("boarding pass is shorter than 10 letters"));
+    }
+
+    Ok(parse_coord_id(line))
+}
+
+/// Seat ids of every boarding pass in the list.
+pub fn parse(input_as_str: &str) -> Result<Vec<i16>, ParseError> {
+    parse_lines(input_as_str, parse_boarding_pass)
+}
+
+pub fn part1(seat_ids: &[i16]) -> Option<i16> {
+    seat_ids.iter().copied().max()
+}
+
+/// The first free seat id after the lowest taken one.
+pub fn part2(seat_ids: &[i16]) -> i16 {
+    let taken_seats = sorted(seat_ids.iter().copied());
+
+    let mut prev: i16 = 0;
+
+    for seat in taken_seats {
+        if prev != 0 && prev != seat - 1 {
+            break;
+        }
+
+        prev = seat;
+    }
+
+    prev + 1
+}
+
+common::fixture_tests!(Day5);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seat_ids() {
+        assert_eq!(parse_coord_id("FBFBBFFRLR"), 357);
+        assert_eq!(parse_coord_id("BFFFBBFRRR"), 567);
+        assert_eq!(parse_coord_id("FFFBBBFRRR"), 119);
+        assert_eq!(parse_coord_id("BBFFBBFRLL"), 820);
+    }
+
+    #[test]
+    fn finds_the_missing_seat() {
+        assert_eq!(part2(&[117, 118, 120, 121]), 119);
+    }
+
+    #[test]
+    fn rejects_malformed_passes() {
+        assert_eq!(
+            parse("FBFBBFFRLR\nFBFBBFRRLR").unwrap_err().to_string(),
+            "2:7: unexpected 'R' in boarding pass"
+        );
+        assert_eq!(parse("FBFBBFFRL").unwrap_err().line, Some(1));
+        assert_eq!(parse("FBFBBFFRLRL").unwrap_err().column, Some(11));
+    }
+}
@@ -0,0 +1,118 @@
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::error::Error;
+
+use common::{Answer, ParseError, Solution};
+
+pub struct Day6;
+
+impl Solution for Day6 {
+    const DAY: u8 = 6;
+    const EXAMPLE: &'static str = include_str!("../fixtures/example.txt");
+    type Input = Vec<Group>;
+
+    fn parse(&self, input: &str) -> Result<Vec<Group>, Box<dyn Error>> {
+        Ok(parse(input)?)
+    }
+
+    fn part1(&self, input: &Vec<Group>) -> Result<Answer, Box<dyn Error>> {
+        Ok(Answer::new(part1(input)))
+    }
+
+    fn part2(&self, input: &Vec<Group>) -> Result<Answer, Box<dyn Error>> {
+        Ok(Answer::new(part2(input)))
+    }
+}
+
+/// The answers of each person in a group, one string per person.
+pub type Group = Vec<String>;
+
+/// Splits the answers into groups, which are separated by blank lines. Each answer must be a
+/// question letter from `a` to `z`.
+pub fn parse(input_as_str: &str) -> Result<Vec<Group>, ParseError> {
+    let mut groups = Vec::new();
+    let mut group = Group::new();
+
+    for (index, line) in input_as_str.lines().enumerate() {
+        if let Some((column, c)) = line
+            .trim()
+            .chars()
+            .enumerate()
+            .find(|(_, c)| !c.is_ascii_lowercase())
+        {
+            let leading = line.len() - line.trim_start().len();
+            return Err(ParseError::new(format!("unexpected {:?}", c))
+                .at_line(index + 1)
+                .at_column(leading + column + 1));
+        }
+
+        if line.trim().is_empty() {
+            if !group.is_empty() {
+                groups.push(group);
+                group = Group::new();
+            }
+        } else {
+            group.push(line.trim().to_string());
+        }
+    }
+
+    if !group.is_empty() {
+        groups.push(group);
+    }
+
+    Ok(groups)
+}
+
+/// Sum over the groups of the questions anyone answered yes to.
+pub fn part1(groups: &[Group]) -> usize {
+    let mut counter = 0;
+
+    for group in groups {
+        let set: HashSet<char> = group.iter().flat_map(|person| person.chars()).collect();
+        counter += set.len();
+    }
+
+    counter
+}
+
+fn all_yes(people: usize, map: &HashMap<char, usize>) -> usize {
+    let mut counter = 0;
+    for val in map.values() {
+        if val == &people {
+            counter += 1;
+        }
+    }
+
+    counter
+}
+
+/// Sum over the groups of the questions everyone answered yes to.
+pub fn part2(groups: &[Group]) -> usize {
+    let mut counter = 0;
+    for group in groups {
+        let mut map = HashMap::new();
+        for c in group.iter().flat_map(|person| person.chars()) {
+            *map.entry(c).or_insert(0) += 1;
+        }
+        counter += all_yes(group.len(), &map);
+    }
+    counter
+}
+
+common::fixture_tests!(Day6);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn separates_groups_at_blank_lines() {
+        assert_eq!(parse(Day6::EXAMPLE).unwrap().len(), 5);
+    }
+
+    #[test]
+    fn rejects_unknown_answers() {
+        let err = parse("abc\n\na b\n").unwrap_err();
+        assert_eq!((err.line, err.column), (Some(3), Some(2)));
+    }
+}
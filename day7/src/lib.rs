@@ -0,0 +1,171 @@
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::error::Error;
+
+use common::{parse_lines, parse_number, Answer, ParseError, Solution};
+
+pub struct Day7;
+
+impl Solution for Day7 {
+    const DAY: u8 = 7;
+    const EXAMPLE: &'static str = include_str!("../fixtures/example.txt");
+    type Input = Vec<Bag>;
+
+    fn parse(&self, input: &str) -> Result<Vec<Bag>, Box<dyn Error>> {
+        Ok(parse(input)?)
+    }
+
+    fn part1(&self, input: &Vec<Bag>) -> Result<Answer, Box<dyn Error>> {
+        Ok(Answer::new(part1(input)))
+    }
+
+    fn part2(&self, input: &Vec<Bag>) -> Result<Answer, Box<dyn Error>> {
+        Ok(Answer::new(part2(input)))
+    }
+}
+
+/// One rule: the bag colour and how many of each other colour it must directly contain.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bag {
+    pub name: String,
+    pub contains: HashMap<String, usize>,
+}
+
+pub fn parse(input_as_str: &str) -> Result<Vec<Bag>, ParseError> {
+    parse_lines(input_as_str, read_bag)
+}
+
+/// Reads a rule such as `light red bags contain 1 bright white bag, 2 muted yellow bags.`
+pub fn read_bag(input: &str) -> Result<Bag, ParseError> {
+    const CONTAIN: &str = " bags contain ";
+
+    let split = input.find(CONTAIN).ok_or_else(|| {
+        ParseError::new("expected \"<colour> bags contain <contents>.\"").at_column(1)
+    })?;
+    let name = input[..split].to_string();
+    let mut contains = HashMap::new();
+
+    let rules_start = split + CONTAIN.len();
+    let rules = input[rules_start..].trim_end_matches('.');
+    if rules == "no other bags" {
+        return Ok(Bag { contains, name });
+    }
+
+    let mut column = rules_start + 1;
+    for rule in rules.split(", ") {
+        let (count, inner) = rule.split_once(' ').unwrap_or((rule, ""));
+        let count: usize = parse_number(count, column)?;
+        let inner_name = inner
+            .trim_end_matches("bags")
+            .trim_end_matches("bag")
+            .trim_end();
+        if inner_name.is_empty() {
+            return Err(ParseError::new(format!(
+                "expected \"<count> <colour> bags\", found {:?}",
+                rule
+            ))
+            .at_column(column));
+        }
+
+        contains.insert(inner_name.to_string(), count);
+        column += rule.len() + 2;
+    }
+
+    Ok(Bag { contains, name })
+}
+
+fn all_possible_containing_bags(
+    initial_bag: &str,
+    bags_to_can_be_contained_by: &HashMap<&String, HashSet<String>>,
+) -> HashSet<String> {
+    let mut complete_set = HashSet::new();
+
+    fn all_possible_containing_bags_rec(
+        current_bag: &str,
+        bags_to_can_be_contained_by: &HashMap<&String, HashSet<String>>,
+        complete_set: &mut std::collections::HashSet<std::string::String>,
+    ) {
+        if complete_set.contains(current_bag) {
+            return;
+        }
+
+        complete_set.insert(current_bag.to_string());
+
+        if let Some(possible_containers) = bags_to_can_be_contained_by.get(&current_bag.to_string())
+        {
+            for bag in possible_containers.iter() {
+                all_possible_containing_bags_rec(bag, bags_to_can_be_contained_by, complete_set)
+            }
+        }
+    }
+
+    all_possible_containing_bags_rec(initial_bag, bags_to_can_be_contained_by, &mut complete_set);
+
+    complete_set.remove(initial_bag);
+    complete_set
+}
+
+fn bags_within_bag(
+    target_bag: &str,
+    bag_rules: &HashMap<&String, &HashMap<String, usize>>,
+) -> usize {
+    if let Some(target_rule) = bag_rules.get(&target_bag.to_string()) {
+        target_rule
+            .iter()
+            .map(|kv| kv.1 * (1 + bags_within_bag(kv.0, bag_rules)))
+            .sum()
+    } else {
+        0
+    }
+}
+
+fn bag_rules(bags: &[Bag]) -> HashMap<&String, &HashMap<String, usize>> {
+    bags.iter().map(|bag| (&bag.name, &bag.contains)).collect()
+}
+
+/// Number of bag colours that can eventually contain a shiny gold bag.
+pub fn part1(bags: &[Bag]) -> usize {
+    let bag_rules = bag_rules(bags);
+
+    let mut bags_to_can_be_contained_by: HashMap<&String, HashSet<String>> =
+        bag_rules.iter().map(|kv| (*kv.0, HashSet::new())).collect();
+
+    for (bag, contains) in &bag_rules {
+        for (inner_bag, _) in contains.iter() {
+            // A colour only ever mentioned inside other bags has no rule of its own.
+            bags_to_can_be_contained_by
+                .entry(inner_bag)
+                .or_default()
+                .insert(bag.to_string());
+        }
+    }
+
+    all_possible_containing_bags("shiny gold", &bags_to_can_be_contained_by).len()
+}
+
+/// Number of bags required inside a single shiny gold bag.
+pub fn part2(bags: &[Bag]) -> usize {
+    bags_within_bag("shiny gold", &bag_rules(bags))
+}
+
+common::fixture_tests!(Day7);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_rules() {
+        let bags = parse(Day7::EXAMPLE).unwrap();
+        assert_eq!(bags[0].name, "light red");
+        assert_eq!(bags[0].contains["muted yellow"], 2);
+    }
+
+    #[test]
+    fn reports_malformed_rules() {
+        let err = parse("faded blue bags contain no other bags.\nshiny gold bags contain 2 dark red bags, some plaid bags.").unwrap_err();
+        assert_eq!(err.to_string(), "2:42: expected a number, found \"some\"");
+        let err = parse("shiny gold bags hold 2 dark red bags.").unwrap_err();
+        assert_eq!((err.line, err.column), (Some(1), Some(1)));
+    }
+}
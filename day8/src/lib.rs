@@ -0,0 +1,105 @@
+use std::collections::HashSet;
+use std::error::Error;
+
+use common::{parse_lines, Answer, ParseError, Solution};
+use gamejoy::executor::GameJoy;
+use gamejoy::executor::Machine;
+use gamejoy::parser;
+
+pub struct Day8;
+
+impl Solution for Day8 {
+    const DAY: u8 = 8;
+    const EXAMPLE: &'static str = include_str!("../fixtures/example.txt");
+    type Input = Vec<parser::OpCode>;
+
+    fn parse(&self, input: &str) -> Result<Vec<parser::OpCode>, Box<dyn Error>> {
+        Ok(parse(input)?)
+    }
+
+    fn part1(&self, program: &Vec<parser::OpCode>) -> Result<Answer, Box<dyn Error>> {
+        Ok(Answer::new(part1(program)?))
+    }
+
+    fn part2(&self, program: &Vec<parser::OpCode>) -> Result<Answer, Box<dyn Error>> {
+        Ok(Answer::new(part2(program)?))
+    }
+}
+
+/// Reads the boot code, one instruction per line.
+pub fn parse(input_as_str: &str) -> Result<Vec<parser::OpCode>, ParseError> {
+    parse_lines(input_as_str, |line| {
+        parser::parse_line(line).ok_or_else(|| {
+            ParseError::new(format!(
+                "expected an instruction such as \"acc +3\", found {:?}",
+                line
+            ))
+        })
+    })
+}
+
+fn run_until_loop(machine: &mut GameJoy) -> Result<i32, ()> {
+    let mut hit_lines: HashSet<usize> = HashSet::new();
+
+    loop {
+        if !hit_lines.insert(machine.instruction_pointer) {
+            return Err(());
+        }
+
+        if let Err(code) = machine.next() {
+            return Ok(code);
+        }
+    }
+}
+
+/// Accumulator value just before any instruction runs a second time.
+pub fn part1(program: &[parser::OpCode]) -> Result<i32, String> {
+    let mut machine = GameJoy::new(program.to_vec());
+    match run_until_loop(&mut machine) {
+        Ok(return_code) => Err(format!("program returned with code {}", return_code)),
+        Err(_) => Ok(machine.accumulator),
+    }
+}
+
+/// Accumulator value once the program terminates after flipping a single `jmp` or `nop`.
+pub fn part2(program: &[parser::OpCode]) -> Result<i32, String> {
+    for (index, op) in program
+        .iter()
+        .enumerate()
+        .filter(|(_, op)| matches!(op, parser::OpCode::Jmp(_) | parser::OpCode::Nop(_)))
+    {
+        let mut prog_copy = program.to_vec();
+        match op {
+            parser::OpCode::Jmp(jmp) => {
+                *prog_copy.get_mut(index).unwrap() = parser::OpCode::Nop(*jmp);
+            }
+            parser::OpCode::Nop(nop) => {
+                *prog_copy.get_mut(index).unwrap() = parser::OpCode::Jmp(*nop);
+            }
+            _ => {}
+        }
+
+        let mut machine = GameJoy::new(prog_copy);
+
+        if let Ok(return_code) = run_until_loop(&mut machine) {
+            if return_code == 0 {
+                return Ok(machine.accumulator);
+            }
+        }
+    }
+
+    Err("no single patch makes the program return".to_string())
+}
+
+common::fixture_tests!(Day8);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_unknown_instructions() {
+        let err = parse("nop +0\nmul +2\n").unwrap_err();
+        assert_eq!(err.line, Some(2));
+    }
+}
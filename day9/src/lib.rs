@@ -0,0 +1,115 @@
+use std::collections::HashSet;
+use std::error::Error;
+
+use common::{parse_lines, parse_number, Answer, ParseError, Solution};
+
+const PREAMBLE: usize = 25;
+
+pub struct Day9;
+
+impl Solution for Day9 {
+    const DAY: u8 = 9;
+    const EXAMPLE: &'static str = include_str!("../fixtures/example.txt");
+    type Input = Vec<i64>;
+
+    fn parse(&self, input: &str) -> Result<Vec<i64>, Box<dyn Error>> {
+        Ok(parse(input)?)
+    }
+
+    fn part1(&self, numbers: &Vec<i64>) -> Result<Answer, Box<dyn Error>> {
+        let first_to_break =
+            first_invalid(numbers, PREAMBLE).ok_or("every number met the condition")?;
+        Ok(Answer::new(first_to_break))
+    }
+
+    fn part2(&self, numbers: &Vec<i64>) -> Result<Answer, Box<dyn Error>> {
+        let first_to_break =
+            first_invalid(numbers, PREAMBLE).ok_or("every number met the condition")?;
+        let range = weakness_range(numbers, first_to_break)
+            .ok_or("no range sums to the first invalid number")?;
+        let smallest = range.iter().min().unwrap();
+        let largest = range.iter().max().unwrap();
+        Ok(Answer::new(smallest + largest)
+            .with_detail("smallest", smallest)
+            .with_detail("largest", largest)
+            .with_detail("length", range.len()))
+    }
+}
+
+pub fn parse(input_as_str: &str) -> Result<Vec<i64>, ParseError> {
+    parse_lines(input_as_str, |line| parse_number(line, 1))
+}
+
+/// The first number that is not the sum of two of the `preamble` numbers before it.
+pub fn first_invalid(numbers: &[i64], preamble: usize) -> Option<i64> {
+    let mut rolling_buffer = vec![];
+    let mut current_numbers = HashSet::new();
+
+    for &as_int in numbers {
+        if rolling_buffer.len() >= preamble {
+            let mut found = false;
+            for prev_val in &rolling_buffer {
+                let target_val = as_int - prev_val;
+                if current_numbers.contains(&target_val) {
+                    found = true;
+                    break;
+                }
+            }
+
+            if !found {
+                return Some(as_int);
+            }
+
+            let removed = rolling_buffer.remove(0);
+            current_numbers.remove(&removed);
+        }
+
+        rolling_buffer.push(as_int);
+        current_numbers.insert(as_int);
+    }
+
+    None
+}
+
+/// Sum of the smallest and largest number in a contiguous range that adds up to `target_value`.
+pub fn encryption_weakness(numbers: &[i64], target_value: i64) -> Option<i64> {
+    let range = weakness_range(numbers, target_value)?;
+    Some(range.iter().min()? + range.iter().max()?)
+}
+
+/// A contiguous range of numbers, each below `target_value`, that adds up to it.
+pub fn weakness_range(numbers: &[i64], target_value: i64) -> Option<Vec<i64>> {
+    let as_ints: Vec<i64> = numbers
+        .iter()
+        .copied()
+        .filter(|val| val < &target_value)
+        .collect();
+    for low in 0..as_ints.len().saturating_sub(1) {
+        for high in low..(as_ints.len() - 1) {
+            let sum: i64 = as_ints[low..high].iter().sum();
+            if sum == target_value {
+                return Some(as_ints[low..high].to_vec());
+            }
+        }
+    }
+
+    None
+}
+
+common::fixture_tests!(Day9);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn short_preamble() {
+        let numbers = [
+            35, 20, 15, 25, 47, 40, 62, 55, 65, 95, 102, 117, 150, 182, 127, 219, 299, 277, 309,
+            576,
+        ];
+        assert_eq!(first_invalid(&numbers, 5), Some(127));
+        assert_eq!(encryption_weakness(&numbers, 127), Some(62));
+        assert_eq!(weakness_range(&numbers, 127), Some(vec![15, 25, 47, 40]));
+    }
+}
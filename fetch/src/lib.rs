@@ -0,0 +1,288 @@
+//! Downloading puzzle inputs from adventofcode.com. Only the `aoc` runner fetches inputs, so this
+//! lives apart from `input`, which every day builds.
+
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use ureq::Agent;
+
+pub const DEFAULT_BASE_URL: &str = "https://adventofcode.com";
+/// Environment variable holding the adventofcode.com session cookie.
+pub const SESSION_VAR: &str = "AOC_SESSION";
+const USER_AGENT: &str =
+    "github.com/pseudonym117/advent-of-code-2020 input fetcher (godtheresnonamesleft@gmail.com)";
+const LAST_REQUEST_FILE: &str = ".last-request";
+
+#[derive(Debug)]
+pub enum FetchError {
+    InvalidDay(u8),
+    Io(PathBuf, io::Error),
+    Http(ureq::Error),
+    /// The server rejected the session token.
+    Unauthorized,
+    /// The puzzle does not exist or is not unlocked yet.
+    NotFound {
+        year: u16,
+        day: u8,
+    },
+    /// The server asked us to slow down, optionally saying for how long.
+    RateLimited(Option<Duration>),
+    Status(u16),
+}
+
+impl fmt::Display for FetchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FetchError::InvalidDay(day) => write!(f, "there is no day {}", day),
+            FetchError::Io(path, err) => write!(f, "{}: {}", path.display(), err),
+            FetchError::Http(err) => write!(f, "request failed: {}", err),
+            FetchError::Unauthorized => write!(f, "session token was rejected"),
+            FetchError::NotFound { year, day } => {
+                write!(f, "{} day {} is not available yet", year, day)
+            }
+            FetchError::RateLimited(Some(wait)) => {
+                write!(f, "rate limited, retry in {}s", wait.as_secs())
+            }
+            FetchError::RateLimited(None) => write!(f, "rate limited"),
+            FetchError::Status(status) => write!(f, "unexpected HTTP status {}", status),
+        }
+    }
+}
+
+impl Error for FetchError {}
+
+impl From<ureq::Error> for FetchError {
+    fn from(err: ureq::Error) -> Self {
+        FetchError::Http(err)
+    }
+}
+
+/// Downloads puzzle inputs into a local cache, one file per year and day.
+///
+/// A cached input is never downloaded again, and consecutive downloads are spaced at least
+/// `min_interval` apart, even across separate runs, by remembering when the last one happened.
+pub struct Fetcher {
+    session: String,
+    cache_dir: PathBuf,
+    base_url: String,
+    min_interval: Duration,
+}
+
+impl Fetcher {
+    pub fn new<P: Into<PathBuf>>(session: &str, cache_dir: P) -> Fetcher {
+        Fetcher {
+            session: session.trim().to_string(),
+            cache_dir: cache_dir.into(),
+            base_url: DEFAULT_BASE_URL.to_string(),
+            min_interval: Duration::from_secs(5),
+        }
+    }
+
+    /// Points the fetcher at another server, such as a local stand-in during tests.
+    pub fn with_base_url(mut self, base_url: &str) -> Fetcher {
+        self.base_url = base_url.trim_end_matches('/').to_string();
+        self
+    }
+
+    pub fn with_min_interval(mut self, min_interval: Duration) -> Fetcher {
+        self.min_interval = min_interval;
+        self
+    }
+
+    /// Where the input for `year`/`day` lives once fetched.
+    pub fn cached_path(&self, year: u16, day: u8) -> PathBuf {
+        cached_path(&self.cache_dir, year, day)
+    }
+
+    /// Returns the path of the cached input, downloading it first if it is not cached yet.
+    pub fn fetch(&self, year: u16, day: u8) -> Result<PathBuf, FetchError> {
+        if !(1..=25).contains(&day) {
+            return Err(FetchError::InvalidDay(day));
+        }
+
+        let path = self.cached_path(year, day);
+        if path.exists() {
+            return Ok(path);
+        }
+
+        let input = self.download(year, day)?;
+
+        let dir = path.parent().unwrap();
+        fs::create_dir_all(dir).map_err(|err| FetchError::Io(dir.to_path_buf(), err))?;
+        // Write then rename, so an interrupted download never leaves a truncated input cached.
+        let partial = path.with_extension("part");
+        fs::write(&partial, input).map_err(|err| FetchError::Io(partial.clone(), err))?;
+        fs::rename(&partial, &path).map_err(|err| FetchError::Io(path.clone(), err))?;
+
+        Ok(path)
+    }
+
+    fn download(&self, year: u16, day: u8) -> Result<String, FetchError> {
+        self.wait_for_slot()?;
+
+        let agent: Agent = Agent::config_builder()
+            .http_status_as_error(false)
+            .timeout_global(Some(Duration::from_secs(30)))
+            .build()
+            .into();
+        let url = format!("{}/{}/day/{}/input", self.base_url, year, day);
+        let mut response = agent
+            .get(&url)
+            .header("Cookie", &format!("session={}", self.session))
+            .header("User-Agent", USER_AGENT)
+            .call()?;
+
+        match response.status().as_u16() {
+            200 => Ok(response.body_mut().read_to_string()?),
+            400 | 401 | 403 => Err(FetchError::Unauthorized),
+            404 => Err(FetchError::NotFound { year, day }),
+            429 => Err(FetchError::RateLimited(
+                response
+                    .headers()
+                    .get("Retry-After")
+                    .and_then(|value| value.to_str().ok())
+                    .and_then(|value| value.trim().parse().ok())
+                    .map(Duration::from_secs),
+            )),
+            status => Err(FetchError::Status(status)),
+        }
+    }
+
+    /// Sleeps until `min_interval` has passed since the previous download, then records this one.
+    fn wait_for_slot(&self) -> Result<(), FetchError> {
+        let marker = self.cache_dir.join(LAST_REQUEST_FILE);
+        let last = fs::read_to_string(&marker)
+            .ok()
+            .and_then(|millis| millis.trim().parse().ok())
+            .map(Duration::from_millis);
+
+        if let Some(last) = last {
+            let elapsed = now().saturating_sub(last);
+            if elapsed < self.min_interval {
+                thread::sleep(self.min_interval - elapsed);
+            }
+        }
+
+        fs::create_dir_all(&self.cache_dir)
+            .and_then(|_| fs::write(&marker, now().as_millis().to_string()))
+            .map_err(|err| FetchError::Io(marker, err))
+    }
+}
+
+pub fn cached_path(cache_dir: &Path, year: u16, day: u8) -> PathBuf {
+    cache_dir
+        .join(year.to_string())
+        .join(format!("day{:02}.txt", day))
+}
+
+fn now() -> Duration {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::sync::mpsc::{self, Receiver};
+    use std::time::Instant;
+
+    /// A stand-in for adventofcode.com: answers each connection with the next canned
+    /// `(status, body)` and forwards the request line and cookie it received.
+    fn serve(responses: Vec<(u16, &'static str)>) -> (String, Receiver<(String, String)>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        let (sender, requests) = mpsc::channel();
+
+        thread::spawn(move || {
+            for (status, body) in responses {
+                let (stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream);
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+
+                let mut cookie = String::new();
+                loop {
+                    let mut header = String::new();
+                    reader.read_line(&mut header).unwrap();
+                    if header.trim().is_empty() {
+                        break;
+                    }
+                    if let Some(value) = header.strip_prefix("cookie: ") {
+                        cookie = value.trim().to_string();
+                    }
+                }
+                sender
+                    .send((request_line.trim().to_string(), cookie))
+                    .unwrap();
+
+                write!(
+                    reader.get_mut(),
+                    "HTTP/1.1 {} X\r\nContent-Length: {}\r\nRetry-After: 60\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                )
+                .unwrap();
+            }
+        });
+
+        (base_url, requests)
+    }
+
+    #[test]
+    fn downloads_once_then_serves_from_cache() {
+        let cache = tempfile::tempdir().unwrap();
+        let (base_url, requests) = serve(vec![(200, "1721\n979\n")]);
+        let fetcher = Fetcher::new("secret\n", cache.path())
+            .with_base_url(&base_url)
+            .with_min_interval(Duration::from_millis(0));
+
+        let path = fetcher.fetch(2020, 1).unwrap();
+        assert_eq!(path, cache.path().join("2020").join("day01.txt"));
+        assert_eq!(fs::read_to_string(&path).unwrap(), "1721\n979\n");
+        assert_eq!(
+            requests.recv().unwrap(),
+            (
+                "GET /2020/day/1/input HTTP/1.1".to_string(),
+                "session=secret".to_string()
+            )
+        );
+
+        // The stand-in only answers once, so a second download would fail.
+        assert_eq!(fetcher.fetch(2020, 1).unwrap(), path);
+        assert!(requests.try_recv().is_err());
+    }
+
+    #[test]
+    fn maps_error_statuses_and_spaces_out_requests() {
+        let cache = tempfile::tempdir().unwrap();
+        let (base_url, _requests) = serve(vec![(404, ""), (429, "")]);
+        let fetcher = Fetcher::new("secret", cache.path())
+            .with_base_url(&base_url)
+            .with_min_interval(Duration::from_millis(200));
+
+        let start = Instant::now();
+        match fetcher.fetch(2020, 2) {
+            Err(FetchError::NotFound { year: 2020, day: 2 }) => {}
+            other => panic!("expected not found, got {:?}", other),
+        }
+        match fetcher.fetch(2020, 3) {
+            Err(FetchError::RateLimited(Some(wait))) => assert_eq!(wait.as_secs(), 60),
+            other => panic!("expected rate limiting, got {:?}", other),
+        }
+        assert!(start.elapsed() >= Duration::from_millis(200));
+        assert!(!fetcher.cached_path(2020, 2).exists());
+        assert!(matches!(
+            fetcher.fetch(2020, 26),
+            Err(FetchError::InvalidDay(26))
+        ));
+    }
+}
@@ -0,0 +1,223 @@
+//! The build script half of the fixture tests: turns the cases in a day's `fixtures` directory
+//! into one `#[test]` each. `common::fixture_tests!` includes the generated tests and supplies the
+//! `check` they call.
+//!
+//! A case is a pair of files sharing a name: `<name>.txt` holds the puzzle input and
+//! `<name>.expected` the answers it should produce, one `key: value` per line. `part1` and
+//! `part2` are the answers, and `part1.<name>` or `part2.<name>` is a detail the answer must
+//! carry. Blank lines and lines starting with `#` are ignored, and anything not listed is not
+//! checked. The `.expected` files are read here, so a malformed one fails the build.
+//!
+//! Every day crate uses `generate.rs` next to this crate's manifest as its build script.
+
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const DIR: &str = "fixtures";
+
+/// One value a fixture expects: the answer to `part` (1 or 2), or the named detail of it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Expectation {
+    pub part: u8,
+    pub detail: Option<String>,
+    pub value: String,
+}
+
+/// The expectations of an `.expected` file, or the 1-based line of the first malformed one with
+/// what is wrong with it.
+pub fn parse_expected(text: &str) -> Result<Vec<Expectation>, (usize, String)> {
+    let mut expectations = Vec::new();
+
+    for (index, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let error = |message: &str| Err((index + 1, message.to_string()));
+        let (key, value) = match line.split_once(':') {
+            Some((key, value)) => (key.trim(), value.trim()),
+            None => return error("expected \"<key>: <value>\""),
+        };
+        let (part, detail) = match key.split_once('.') {
+            Some((part, detail)) => (part, Some(detail.to_string())),
+            None => (key, None),
+        };
+        let part = match part {
+            "part1" => 1,
+            "part2" => 2,
+            _ => return error("keys start with \"part1\" or \"part2\""),
+        };
+
+        expectations.push(Expectation {
+            part,
+            detail,
+            value: value.to_string(),
+        });
+    }
+
+    Ok(expectations)
+}
+
+/// Writes the tests for `$CARGO_MANIFEST_DIR/fixtures` to `$OUT_DIR/fixtures.rs`.
+pub fn generate() {
+    let manifest_dir = PathBuf::from(env::var_os("CARGO_MANIFEST_DIR").unwrap());
+    let dir = manifest_dir.join(DIR);
+    // Cargo rescans a directory named here, so adding or editing a case reruns the build script.
+    println!("cargo:rerun-if-changed={}", dir.display());
+
+    let out = PathBuf::from(env::var_os("OUT_DIR").unwrap()).join("fixtures.rs");
+    if let Err(err) = fs::write(&out, tests(&dir)) {
+        panic!("{}: {}", out.display(), err);
+    }
+}
+
+/// The source of one test per case in `dir`; no tests if `dir` does not exist.
+pub fn tests(dir: &Path) -> String {
+    let mut expected_files: Vec<PathBuf> = match fs::read_dir(dir) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "expected"))
+            .collect(),
+        Err(_) => return String::new(),
+    };
+    expected_files.sort();
+
+    let mut code = String::new();
+    let mut names: HashMap<String, PathBuf> = HashMap::new();
+    for expected in expected_files {
+        let name = expected.file_stem().unwrap().to_string_lossy();
+        let test = test_name(&name);
+        if let Some(other) = names.insert(test.clone(), expected.clone()) {
+            panic!(
+                "fixtures {} and {} would both be tested as {}; rename one of them",
+                other.display(),
+                expected.display(),
+                test
+            );
+        }
+        let input = expected.with_extension("txt");
+        if !input.exists() {
+            panic!(
+                "fixture {:?} has no input, expected {}",
+                name,
+                input.display()
+            );
+        }
+
+        let text = fs::read_to_string(&expected)
+            .unwrap_or_else(|err| panic!("{}: {}", expected.display(), err));
+        let expectations = parse_expected(&text).unwrap_or_else(|(line, message)| {
+            panic!("{}:{}: {}", expected.display(), line, message)
+        });
+        let expectations: Vec<String> = expectations
+            .iter()
+            .map(|expectation| {
+                format!(
+                    "({}, {:?}, {:?})",
+                    expectation.part,
+                    expectation.detail.as_deref(),
+                    expectation.value
+                )
+            })
+            .collect();
+
+        code.push_str(&format!(
+            "#[test]\nfn {}() {{\n    check({:?}, include_str!({:?}), &[{}]);\n}}\n",
+            test,
+            name,
+            input.display().to_string(),
+            expectations.join(", ")
+        ));
+    }
+
+    code
+}
+
+/// `fixture_` followed by the case name with anything that cannot be in an identifier replaced.
+fn test_name(name: &str) -> String {
+    let name: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    format!("fixture_{}", name.to_lowercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generates_one_test_per_case() {
+        let dir = tempfile::tempdir().unwrap();
+        for file in &["example.txt", "no-bags.txt", "no-bags.expected"] {
+            fs::write(dir.path().join(file), "").unwrap();
+        }
+        fs::write(
+            dir.path().join("example.expected"),
+            "# example\npart1: 5\npart2.range: 3 \"7\"\n",
+        )
+        .unwrap();
+        fs::write(dir.path().join("notes.md"), "").unwrap();
+
+        let code = tests(dir.path());
+        assert_eq!(code.matches("#[test]").count(), 2);
+        assert!(code.contains("fn fixture_example() {\n    check(\"example\", include_str!("));
+        assert!(code.contains(", &[(1, None, \"5\"), (2, Some(\"range\"), \"3 \\\"7\\\"\")]);\n"));
+        assert!(code.contains("fn fixture_no_bags() {"));
+        assert_eq!(tests(&dir.path().join("missing")), "");
+    }
+
+    #[test]
+    fn rejects_cases_with_the_same_test_name() {
+        let dir = tempfile::tempdir().unwrap();
+        for file in &[
+            "no-bags.txt",
+            "no-bags.expected",
+            "no_bags.txt",
+            "no_bags.expected",
+        ] {
+            fs::write(dir.path().join(file), "").unwrap();
+        }
+
+        let message = std::panic::catch_unwind(|| tests(dir.path()))
+            .unwrap_err()
+            .downcast::<String>()
+            .unwrap();
+        assert_eq!(
+            *message,
+            format!(
+                "fixtures {} and {} would both be tested as fixture_no_bags; rename one of them",
+                dir.path().join("no-bags.expected").display(),
+                dir.path().join("no_bags.expected").display()
+            )
+        );
+    }
+
+    #[test]
+    fn parses_answers_and_details() {
+        let expected = parse_expected("# example\npart1: 5\n\npart2.range: 3 7\n").unwrap();
+        assert_eq!(
+            expected,
+            [
+                Expectation {
+                    part: 1,
+                    detail: None,
+                    value: "5".to_string(),
+                },
+                Expectation {
+                    part: 2,
+                    detail: Some("range".to_string()),
+                    value: "3 7".to_string(),
+                },
+            ]
+        );
+        assert_eq!(parse_expected("\npart3: 1").unwrap_err().0, 2);
+        assert_eq!(
+            parse_expected("part1 5").unwrap_err(),
+            (1, "expected \"<key>: <value>\"".to_string())
+        );
+    }
+}
@@ -1,24 +1,155 @@
 use crate::parser::OpCode;
+use crate::watch::{EvalError, Expr, State};
 
 pub trait Machine {
     fn next(&mut self) -> Result<(), i32>;
     fn reset(&mut self);
 }
 
+/// A change in the value of a watch expression, recorded after the step that caused it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WatchEvent {
+    pub watch: usize,
+    pub step: usize,
+    pub instruction_pointer: usize,
+    pub value: Result<i64, EvalError>,
+}
+
+struct Watch {
+    expr: Expr,
+    last: Option<Result<i64, EvalError>>,
+}
+
+/// Why `GameJoy::run` returned.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Stop {
+    /// The listed breakpoints held after the last step.
+    Breakpoint(Vec<usize>),
+    /// The machine stopped with this code, as returned by `Machine::next`.
+    Exited(i32),
+}
+
 pub struct GameJoy {
     pub accumulator: i32,
     pub instruction_pointer: usize,
     loaded_program: Vec<OpCode>,
     pub error: Option<i32>,
+    steps: usize,
+    hits: Vec<usize>,
+    breakpoints: Vec<Option<Expr>>,
+    watches: Vec<Option<Watch>>,
+    triggered: Vec<usize>,
+    watch_log: Vec<WatchEvent>,
 }
 
 impl GameJoy {
     pub fn new(program: Vec<OpCode>) -> GameJoy {
+        let hits = vec![0; program.len()];
         GameJoy {
             accumulator: 0,
             instruction_pointer: 0,
             loaded_program: program,
             error: None,
+            steps: 0,
+            hits,
+            breakpoints: vec![],
+            watches: vec![],
+            triggered: vec![],
+            watch_log: vec![],
+        }
+    }
+
+    /// Number of instructions executed since the last reset.
+    pub fn steps(&self) -> usize {
+        self.steps
+    }
+
+    /// Number of times the instruction at `ip` has been executed since the last reset.
+    pub fn hits(&self, ip: usize) -> usize {
+        self.hits.get(ip).copied().unwrap_or(0)
+    }
+
+    /// Adds a breakpoint that triggers after every step where `condition` holds. A condition
+    /// that fails to evaluate also triggers, so the error is not silently ignored.
+    pub fn add_breakpoint(&mut self, condition: Expr) -> usize {
+        self.breakpoints.push(Some(condition));
+        self.breakpoints.len() - 1
+    }
+
+    pub fn remove_breakpoint(&mut self, id: usize) {
+        if let Some(breakpoint) = self.breakpoints.get_mut(id) {
+            *breakpoint = None;
+        }
+    }
+
+    /// Adds an expression whose value is recorded in `watch_log` every time it changes.
+    pub fn add_watch(&mut self, expr: Expr) -> usize {
+        self.watches.push(Some(Watch { expr, last: None }));
+        self.watches.len() - 1
+    }
+
+    pub fn remove_watch(&mut self, id: usize) {
+        if let Some(watch) = self.watches.get_mut(id) {
+            *watch = None;
+        }
+    }
+
+    /// Breakpoints whose condition held after the most recent step.
+    pub fn triggered(&self) -> &[usize] {
+        &self.triggered
+    }
+
+    pub fn watch_log(&self) -> &[WatchEvent] {
+        &self.watch_log
+    }
+
+    /// Steps until a breakpoint triggers or the machine stops. Without breakpoints this only
+    /// returns once the program exits, so looping programs need one to bound the run.
+    pub fn run(&mut self) -> Stop {
+        loop {
+            if let Err(code) = self.next() {
+                return Stop::Exited(code);
+            }
+
+            if !self.triggered.is_empty() {
+                return Stop::Breakpoint(self.triggered.clone());
+            }
+        }
+    }
+
+    fn evaluate_watches(&mut self) {
+        let state = State {
+            accumulator: self.accumulator,
+            instruction_pointer: self.instruction_pointer,
+            steps: self.steps,
+            hits: &self.hits,
+        };
+
+        self.triggered = self
+            .breakpoints
+            .iter()
+            .enumerate()
+            .filter_map(
+                |(id, condition)| match condition.as_ref()?.is_true(&state) {
+                    Ok(false) => None,
+                    _ => Some(id),
+                },
+            )
+            .collect();
+
+        for (id, watch) in self.watches.iter_mut().enumerate() {
+            if let Some(watch) = watch {
+                let value = watch.expr.eval(&state);
+                if watch.last.as_ref() != Some(&value) {
+                    self.watch_log.push(WatchEvent {
+                        watch: id,
+                        step: self.steps,
+                        instruction_pointer: self.instruction_pointer,
+                        value: value.clone(),
+                    });
+                    watch.last = Some(value);
+                }
+            }
         }
     }
 }
@@ -39,6 +170,9 @@ impl Machine for GameJoy {
             }
         }
 
+        self.steps += 1;
+        self.hits[self.instruction_pointer] += 1;
+
         let mut new_ip = self.instruction_pointer + 1;
         match op.unwrap() {
             OpCode::Acc(acc) => self.accumulator += acc,
@@ -55,6 +189,7 @@ impl Machine for GameJoy {
         }
 
         self.instruction_pointer = new_ip;
+        self.evaluate_watches();
         Ok(())
     }
 
@@ -62,5 +197,39 @@ impl Machine for GameJoy {
         self.accumulator = 0;
         self.instruction_pointer = 0;
         self.error = None;
+        self.steps = 0;
+        self.hits.iter_mut().for_each(|hits| *hits = 0);
+        self.triggered.clear();
+        self.watch_log.clear();
+        for watch in self.watches.iter_mut().flatten() {
+            watch.last = None;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser;
+    use crate::watch;
+
+    #[test]
+    fn stops_on_conditional_breakpoint_and_logs_watches() {
+        let program = parser::parse("acc +5\njmp -1").unwrap();
+        let mut machine = GameJoy::new(program);
+        let breakpoint = machine.add_breakpoint(watch::parse("hits(ip=1) == 3").unwrap());
+        machine.add_watch(watch::parse("acc > 10").unwrap());
+
+        assert_eq!(machine.run(), Stop::Breakpoint(vec![breakpoint]));
+        assert_eq!(machine.accumulator, 15);
+        assert_eq!(machine.steps(), 6);
+        assert_eq!(
+            machine
+                .watch_log()
+                .iter()
+                .map(|event| (event.step, event.value.clone()))
+                .collect::<Vec<_>>(),
+            vec![(1, Ok(0)), (5, Ok(1))]
+        );
     }
 }
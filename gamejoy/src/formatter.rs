@@ -0,0 +1,85 @@
+use crate::parser;
+use crate::preprocessor::DIRECTIVES;
+
+/// Rewrites GameJoy source into its canonical form: lowercase opcodes, a single space before the
+/// argument and an explicit sign on every argument (`acc +5`, `jmp -3`, `nop +0`).
+///
+/// Preprocessor directive names are lowercased. Macro invocations, macro parameters in opcode
+/// position (`%op +1`, which must match the parameter's case) and lines that do not parse keep
+/// their text but have their whitespace collapsed, so formatting never changes what a program
+/// means.
+pub fn format(source: &str) -> String {
+    let mut formatted: String = source
+        .lines()
+        .map(format_line)
+        .collect::<Vec<String>>()
+        .join("\n");
+
+    if !formatted.is_empty() {
+        formatted.push('\n');
+    }
+
+    formatted
+}
+
+pub fn is_formatted(source: &str) -> bool {
+    format(source) == source
+}
+
+fn format_line(line: &str) -> String {
+    if let Some(op) = parser::parse_line(line) {
+        if line.split_ascii_whitespace().count() == 2 {
+            return op.to_string();
+        }
+    }
+
+    let mut tokens: Vec<String> = line.split_ascii_whitespace().map(str::to_string).collect();
+    if let Some(first) = tokens.first_mut() {
+        let directive = first
+            .strip_prefix('%')
+            .map(str::to_lowercase)
+            .filter(|name| DIRECTIVES.contains(&name.as_str()));
+        if let Some(directive) = directive {
+            *first = format!("%{}", directive);
+        }
+    }
+
+    tokens.join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::OpCode;
+    use crate::preprocessor::Preprocessor;
+
+    #[test]
+    fn canonicalises_case_spacing_and_signs() {
+        assert_eq!(
+            format("  NOP 0\nacc\t\t5\n\nJmp   -3  \n%Repeat  2\nbump   +1\n"),
+            "nop +0\nacc +5\n\njmp -3\n%repeat 2\nbump +1\n"
+        );
+        assert!(is_formatted("nop +0\nacc -1\n"));
+    }
+
+    #[test]
+    fn keeps_parameters_in_opcode_position() {
+        let source = "%macro  twice OP\n%OP   1\n%OP 1\n%endmacro\ntwice acc\n";
+        let formatted = format(source);
+        assert_eq!(
+            formatted,
+            "%macro twice OP\n%OP 1\n%OP 1\n%endmacro\ntwice acc\n"
+        );
+
+        let expand = |source: &str| {
+            Preprocessor::new()
+                .preprocess_str("main.gj", source)
+                .unwrap()
+                .parse()
+                .unwrap()
+        };
+        assert_eq!(expand(source), [OpCode::Acc(1), OpCode::Acc(1)]);
+        assert_eq!(expand(&formatted), expand(source));
+        assert!(is_formatted(&formatted));
+    }
+}
@@ -6,5 +6,9 @@ mod tests {
     }
 }
 
-pub mod parser;
 pub mod executor;
+pub mod formatter;
+pub mod linter;
+pub mod parser;
+pub mod preprocessor;
+pub mod watch;
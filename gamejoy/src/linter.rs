@@ -0,0 +1,272 @@
+use std::fmt;
+
+use crate::parser::{self, OpCode};
+use crate::preprocessor::{Expanded, Origin};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Rule {
+    InvalidInstruction,
+    SelfLoop,
+    NopArgument,
+    Unreachable,
+    JumpOutOfRange,
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Rule::InvalidInstruction => "invalid-instruction",
+                Rule::SelfLoop => "self-loop",
+                Rule::NopArgument => "nop-argument",
+                Rule::Unreachable => "unreachable",
+                Rule::JumpOutOfRange => "jump-out-of-range",
+            }
+        )
+    }
+}
+
+#[derive(Debug)]
+pub struct Diagnostic {
+    pub origin: Origin,
+    pub rule: Rule,
+    pub message: String,
+    /// Whether `fix` knows how to resolve this diagnostic.
+    pub fixable: bool,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}: {}", self.origin, self.rule, self.message)
+    }
+}
+
+struct Finding {
+    index: usize,
+    rule: Rule,
+    message: String,
+}
+
+/// Checks a preprocessed program for likely mistakes. Line numbers refer to the original source.
+pub fn lint(expanded: &Expanded) -> Vec<Diagnostic> {
+    let mut diagnostics = vec![];
+    let mut program = vec![];
+    let mut positions = vec![];
+
+    for (position, line) in expanded.lines().iter().enumerate() {
+        if line.text.trim().is_empty() {
+            continue;
+        }
+
+        match parser::parse_line(&line.text) {
+            Some(op) => {
+                program.push(op);
+                positions.push(position);
+            }
+            None => diagnostics.push((
+                position,
+                Diagnostic {
+                    origin: line.origin.clone(),
+                    rule: Rule::InvalidInstruction,
+                    message: format!("\"{}\" is not an instruction", line.text.trim()),
+                    fixable: false,
+                },
+            )),
+        }
+    }
+
+    diagnostics.extend(analyse(&program).into_iter().map(|finding| {
+        let position = positions[finding.index];
+        (
+            position,
+            Diagnostic {
+                origin: expanded.lines()[position].origin.clone(),
+                fixable: matches!(finding.rule, Rule::NopArgument | Rule::Unreachable),
+                rule: finding.rule,
+                message: finding.message,
+            },
+        )
+    }));
+
+    // Report in the order the lines appear in the expanded program, which keeps included files
+    // and macro bodies next to the code that pulled them in.
+    diagnostics.sort_by_key(|(position, _)| *position);
+    diagnostics
+        .into_iter()
+        .map(|(_, diagnostic)| diagnostic)
+        .collect()
+}
+
+/// Zeroes `nop` arguments and deletes unreachable instructions, rebasing every jump that crosses a
+/// deleted line. Returns `None` for sources using preprocessor directives, since fixes would have
+/// to be applied to the macro or included file rather than the expanded text.
+pub fn fix(source: &str) -> Option<String> {
+    if source
+        .lines()
+        .any(|line| line.trim_start().starts_with('%'))
+    {
+        return None;
+    }
+
+    let parsed: Vec<Option<OpCode>> = source.lines().map(parser::parse_line).collect();
+    let program: Vec<OpCode> = parsed.iter().flatten().cloned().collect();
+    let reachable = reachable(&program);
+
+    // new_index[i] is where instruction i ends up once unreachable code is gone; the extra entry
+    // keeps jumps that land just past the end (a clean exit) pointing there.
+    let mut new_index = Vec::with_capacity(program.len() + 1);
+    let mut kept = 0;
+    for is_reachable in reachable.iter().chain(std::iter::once(&true)) {
+        new_index.push(kept);
+        if *is_reachable {
+            kept += 1;
+        }
+    }
+
+    let mut fixed = vec![];
+    let mut index = 0;
+    for (line, op) in source.lines().zip(parsed) {
+        let op = match op {
+            Some(op) => op,
+            None => {
+                fixed.push(line.to_string());
+                continue;
+            }
+        };
+
+        if reachable[index] {
+            fixed.push(match op {
+                OpCode::Nop(arg) if arg != 0 => OpCode::Nop(0).to_string(),
+                OpCode::Jmp(rel) => match target(index, rel, program.len()) {
+                    Some(target) if new_index[target] != target || new_index[index] != index => {
+                        let rebased = new_index[target] as i32 - new_index[index] as i32;
+                        OpCode::Jmp(rebased).to_string()
+                    }
+                    _ => line.to_string(),
+                },
+                _ => line.to_string(),
+            });
+        }
+        index += 1;
+    }
+
+    let mut fixed = fixed.join("\n");
+    if source.ends_with('\n') {
+        fixed.push('\n');
+    }
+    Some(fixed)
+}
+
+/// Where a jump from `index` lands, or `None` if it leaves the program (landing exactly one past
+/// the last instruction is a normal exit and is allowed).
+fn target(index: usize, rel: i32, len: usize) -> Option<usize> {
+    let target = index as i64 + rel as i64;
+    if target < 0 || target > len as i64 {
+        None
+    } else {
+        Some(target as usize)
+    }
+}
+
+fn reachable(program: &[OpCode]) -> Vec<bool> {
+    let mut reachable = vec![false; program.len()];
+    let mut pending = vec![0];
+
+    while let Some(index) = pending.pop() {
+        if index >= program.len() || reachable[index] {
+            continue;
+        }
+        reachable[index] = true;
+
+        match program[index] {
+            OpCode::Jmp(rel) => pending.extend(target(index, rel, program.len())),
+            _ => pending.push(index + 1),
+        }
+    }
+
+    reachable
+}
+
+fn analyse(program: &[OpCode]) -> Vec<Finding> {
+    let reachable = reachable(program);
+    let mut findings = vec![];
+
+    for (index, op) in program.iter().enumerate() {
+        match op {
+            OpCode::Jmp(0) => findings.push(Finding {
+                index,
+                rule: Rule::SelfLoop,
+                message: "jmp +0 loops forever".to_string(),
+            }),
+            OpCode::Jmp(rel) if target(index, *rel, program.len()).is_none() => {
+                findings.push(Finding {
+                    index,
+                    rule: Rule::JumpOutOfRange,
+                    message: format!(
+                        "jump to instruction {} is outside the program (0..={})",
+                        index as i64 + *rel as i64,
+                        program.len()
+                    ),
+                })
+            }
+            OpCode::Nop(arg) if *arg != 0 => findings.push(Finding {
+                index,
+                rule: Rule::NopArgument,
+                message: format!("nop has a non-zero argument {:+}", arg),
+            }),
+            _ => {}
+        }
+
+        if !reachable[index] {
+            findings.push(Finding {
+                index,
+                rule: Rule::Unreachable,
+                message: format!("{} can never be executed", op),
+            });
+        }
+    }
+
+    findings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::preprocessor::Preprocessor;
+
+    fn rules(source: &str) -> Vec<(usize, Rule)> {
+        let expanded = Preprocessor::new()
+            .preprocess_str("test.gj", source)
+            .unwrap();
+        lint(&expanded)
+            .iter()
+            .map(|diagnostic| (diagnostic.origin.line, diagnostic.rule))
+            .collect()
+    }
+
+    #[test]
+    fn flags_each_rule_with_its_line() {
+        assert_eq!(
+            rules("nop +3\njmp +2\nacc +1\njmp +0\njmp -9\nbogus"),
+            vec![
+                (1, Rule::NopArgument),
+                (3, Rule::Unreachable),
+                (4, Rule::SelfLoop),
+                (5, Rule::JumpOutOfRange),
+                (5, Rule::Unreachable),
+                (6, Rule::InvalidInstruction),
+            ]
+        );
+    }
+
+    #[test]
+    fn fix_removes_dead_code_and_rebases_jumps() {
+        assert_eq!(
+            fix("nop +4\njmp +3\nacc +1\nacc +2\nacc +3\njmp +1\n").unwrap(),
+            "nop +0\njmp +1\nacc +3\njmp +1\n"
+        );
+        assert!(fix("%repeat 2\nnop +0\n%endrepeat").is_none());
+    }
+}
@@ -0,0 +1,107 @@
+use std::env;
+use std::error::Error;
+use std::fs;
+use std::process;
+
+use gamejoy::formatter;
+use gamejoy::linter;
+use gamejoy::preprocessor::Preprocessor;
+
+const USAGE: &str = "usage: gamejoy fmt [--check] <file>...\n       gamejoy lint [--fix] <file>...";
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let (command, rest) = match args.split_first() {
+        Some((command, rest)) => (command.as_str(), rest),
+        None => usage(),
+    };
+
+    let allowed = match command {
+        "fmt" => "--check",
+        "lint" => "--fix",
+        _ => usage(),
+    };
+    let (flags, files): (Vec<&String>, Vec<&String>) =
+        rest.iter().partition(|arg| arg.starts_with("--"));
+    if files.is_empty() || flags.iter().any(|flag| *flag != allowed) {
+        usage();
+    }
+    let flag = !flags.is_empty();
+
+    let clean = match command {
+        "fmt" => fmt(&files, flag)?,
+        _ => lint(&files, flag)?,
+    };
+
+    if !clean {
+        process::exit(1);
+    }
+    Ok(())
+}
+
+fn usage() -> ! {
+    eprintln!("{}", USAGE);
+    process::exit(2);
+}
+
+/// Formats every file in place, or with `check` only reports the ones that would change.
+fn fmt(files: &[&String], check: bool) -> Result<bool, Box<dyn Error>> {
+    let mut clean = true;
+
+    for file in files {
+        let source = fs::read_to_string(file)?;
+        if formatter::is_formatted(&source) {
+            continue;
+        }
+
+        if check {
+            println!("{}: not formatted", file);
+            clean = false;
+        } else {
+            fs::write(file, formatter::format(&source))?;
+        }
+    }
+
+    Ok(clean)
+}
+
+fn lint(files: &[&String], fix: bool) -> Result<bool, Box<dyn Error>> {
+    let mut clean = true;
+
+    for file in files {
+        let mut source = fs::read_to_string(file)?;
+
+        if fix {
+            match linter::fix(&source) {
+                Some(fixed) => {
+                    if fixed != source {
+                        fs::write(file, &fixed)?;
+                    }
+                    source = fixed;
+                }
+                None => eprintln!("{}: uses preprocessor directives, not fixing", file),
+            }
+        }
+
+        let expanded = match Preprocessor::new().preprocess_str(file, &source) {
+            Ok(expanded) => expanded,
+            Err(err) => {
+                println!("{}", err);
+                clean = false;
+                continue;
+            }
+        };
+
+        for diagnostic in linter::lint(&expanded) {
+            let hint = if diagnostic.fixable && !fix {
+                " (fixable with --fix)"
+            } else {
+                ""
+            };
+            println!("{}{}", diagnostic, hint);
+            clean = false;
+        }
+    }
+
+    Ok(clean)
+}
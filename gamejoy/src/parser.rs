@@ -1,5 +1,4 @@
-use std::borrow::Cow;
-
+#[derive(Debug, PartialEq)]
 pub enum OpCode {
     Nop(i32),
     Acc(i32),
@@ -19,9 +18,9 @@ impl Clone for OpCode {
 impl std::fmt::Display for OpCode {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::result::Result<(), std::fmt::Error> {
         match self {
-            OpCode::Nop(nop) => write!(f, "nop {}", nop),
-            OpCode::Acc(acc) => write!(f, "acc {}", acc),
-            OpCode::Jmp(jmp) => write!(f, "jmp {}", jmp),
+            OpCode::Nop(nop) => write!(f, "nop {:+}", nop),
+            OpCode::Acc(acc) => write!(f, "acc {:+}", acc),
+            OpCode::Jmp(jmp) => write!(f, "jmp {:+}", jmp),
         }
     }
 }
@@ -35,11 +34,12 @@ fn make_op(op: &str, arg: i32) -> Option<OpCode> {
     }
 }
 
-pub fn parse(program: &Cow<str>) -> Option<Vec<OpCode>> {
-    Some(program.lines().filter_map(to_op).collect())
+pub fn parse(program: &str) -> Option<Vec<OpCode>> {
+    Some(program.lines().filter_map(parse_line).collect())
 }
 
-fn to_op(line: &str) -> Option<OpCode> {
+/// Parses a single instruction such as `acc +3`, returning `None` if the line is not one.
+pub fn parse_line(line: &str) -> Option<OpCode> {
     let mut split = line.split_ascii_whitespace().take(2);
     let op_str = split.next()?;
     let arg: i32 = split.next()?.parse().ok()?;
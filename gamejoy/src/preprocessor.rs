@@ -0,0 +1,695 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+use std::rc::Rc;
+
+use crate::parser::{self, OpCode};
+
+const MAX_EXPANSION_DEPTH: usize = 64;
+/// Lines a single program may expand to, counting every pass through a `%repeat` body, so that a
+/// huge repeat count fails instead of exhausting memory.
+const MAX_EXPANDED_LINES: usize = 1_000_000;
+const RESERVED_NAMES: [&str; 3] = ["nop", "acc", "jmp"];
+pub(crate) const DIRECTIVES: [&str; 5] = ["include", "macro", "endmacro", "repeat", "endrepeat"];
+
+/// Location of a line in the original source, before any expansion took place.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Origin {
+    pub file: Rc<PathBuf>,
+    pub line: usize,
+    /// The macro invocation or `%repeat` this line was expanded from, if any.
+    pub expanded_from: Option<Rc<Origin>>,
+}
+
+impl fmt::Display for Origin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.file.display(), self.line)?;
+        if let Some(parent) = &self.expanded_from {
+            write!(f, " (expanded from {})", parent)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Line {
+    pub text: String,
+    pub origin: Origin,
+}
+
+#[derive(Debug)]
+pub enum ErrorKind {
+    Io(io::Error),
+    IncludeCycle(PathBuf),
+    UnknownDirective(String),
+    MalformedDirective(String),
+    UnexpectedDirective(String),
+    Unterminated(&'static str),
+    DuplicateMacro(String),
+    ReservedName(String),
+    UndefinedParameter(String),
+    ArgumentCount {
+        name: String,
+        expected: usize,
+        found: usize,
+    },
+    TooDeep,
+    TooLarge,
+    InvalidInstruction(String),
+}
+
+#[derive(Debug)]
+pub struct PreprocessError {
+    /// `None` only when the root file itself could not be read.
+    pub origin: Option<Origin>,
+    pub kind: ErrorKind,
+}
+
+impl fmt::Display for PreprocessError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(origin) = &self.origin {
+            write!(f, "{}: ", origin)?;
+        }
+
+        match &self.kind {
+            ErrorKind::Io(err) => write!(f, "{}", err),
+            ErrorKind::IncludeCycle(path) => write!(f, "{} includes itself", path.display()),
+            ErrorKind::UnknownDirective(name) => write!(f, "unknown directive %{}", name),
+            ErrorKind::MalformedDirective(msg) => write!(f, "malformed directive: {}", msg),
+            ErrorKind::UnexpectedDirective(name) => write!(f, "unexpected %{}", name),
+            ErrorKind::Unterminated(block) => write!(f, "%{} is never closed", block),
+            ErrorKind::DuplicateMacro(name) => write!(f, "macro {} is already defined", name),
+            ErrorKind::ReservedName(name) => write!(f, "{} cannot be used as a macro name", name),
+            ErrorKind::UndefinedParameter(name) => write!(f, "undefined macro parameter %{}", name),
+            ErrorKind::ArgumentCount {
+                name,
+                expected,
+                found,
+            } => write!(
+                f,
+                "macro {} takes {} argument(s) but {} were given",
+                name, expected, found
+            ),
+            ErrorKind::TooDeep => write!(
+                f,
+                "expansion nested more than {} levels deep",
+                MAX_EXPANSION_DEPTH
+            ),
+            ErrorKind::TooLarge => write!(f, "expansion exceeds {} lines", MAX_EXPANDED_LINES),
+            ErrorKind::InvalidInstruction(line) => write!(f, "invalid instruction \"{}\"", line),
+        }
+    }
+}
+
+impl Error for PreprocessError {}
+
+fn error(origin: &Origin, kind: ErrorKind) -> PreprocessError {
+    PreprocessError {
+        origin: Some(origin.clone()),
+        kind,
+    }
+}
+
+/// Result of preprocessing: plain GameJoy source where every line remembers where it came from.
+pub struct Expanded {
+    lines: Vec<Line>,
+}
+
+impl Expanded {
+    pub fn lines(&self) -> &[Line] {
+        &self.lines
+    }
+
+    pub fn text(&self) -> String {
+        self.lines
+            .iter()
+            .map(|line| line.text.as_str())
+            .collect::<Vec<&str>>()
+            .join("\n")
+    }
+
+    /// Parses the expanded program, skipping blank lines. Unlike `parser::parse`, lines that are
+    /// not valid instructions are reported against their original location instead of dropped.
+    pub fn parse(&self) -> Result<Vec<OpCode>, PreprocessError> {
+        self.lines
+            .iter()
+            .filter(|line| !line.text.trim().is_empty())
+            .map(|line| {
+                parser::parse_line(&line.text).ok_or_else(|| {
+                    error(
+                        &line.origin,
+                        ErrorKind::InvalidInstruction(line.text.trim().to_string()),
+                    )
+                })
+            })
+            .collect()
+    }
+}
+
+struct Macro {
+    params: Vec<String>,
+    body: Vec<Line>,
+}
+
+type Loader = Box<dyn Fn(&Path) -> io::Result<String>>;
+
+/// Expands `%include "file"`, `%macro name args ... %endmacro` and `%repeat n ... %endrepeat`.
+///
+/// Macro parameters are referenced in the body as `%name` and substituted token by token; a
+/// macro is invoked by writing its name where an opcode would go, followed by its arguments.
+pub struct Preprocessor {
+    loader: Loader,
+    macros: HashMap<String, Macro>,
+    include_stack: Vec<PathBuf>,
+    /// Lines expanded so far by the current call, see `MAX_EXPANDED_LINES`.
+    expanded: usize,
+}
+
+impl Default for Preprocessor {
+    fn default() -> Self {
+        Preprocessor::new()
+    }
+}
+
+impl Preprocessor {
+    pub fn new() -> Preprocessor {
+        Preprocessor::with_loader(|path| fs::read_to_string(path))
+    }
+
+    /// Uses `loader` instead of the filesystem to resolve the root file and every `%include`.
+    pub fn with_loader<F>(loader: F) -> Preprocessor
+    where
+        F: Fn(&Path) -> io::Result<String> + 'static,
+    {
+        Preprocessor {
+            loader: Box::new(loader),
+            macros: HashMap::new(),
+            include_stack: vec![],
+            expanded: 0,
+        }
+    }
+
+    pub fn preprocess_file<P: AsRef<Path>>(
+        &mut self,
+        path: P,
+    ) -> Result<Expanded, PreprocessError> {
+        let path = path.as_ref().to_path_buf();
+        let source = (self.loader)(&path).map_err(|err| PreprocessError {
+            origin: None,
+            kind: ErrorKind::Io(err),
+        })?;
+
+        self.preprocess_str(path, &source)
+    }
+
+    /// Preprocesses `source` as if it had been read from `path`; includes resolve relative to it.
+    pub fn preprocess_str<P: AsRef<Path>>(
+        &mut self,
+        path: P,
+        source: &str,
+    ) -> Result<Expanded, PreprocessError> {
+        let path = path.as_ref().to_path_buf();
+        let lines = to_lines(&path, source);
+
+        let mut out = vec![];
+        self.expanded = 0;
+        self.include_stack.push(normalise(&path));
+        let result = self.expand(&lines, &mut out, 0);
+        self.include_stack.pop();
+        result?;
+
+        Ok(Expanded { lines: out })
+    }
+
+    fn expand(
+        &mut self,
+        lines: &[Line],
+        out: &mut Vec<Line>,
+        depth: usize,
+    ) -> Result<(), PreprocessError> {
+        let mut index = 0;
+
+        while index < lines.len() {
+            let line = &lines[index];
+            self.count_line(line)?;
+            let mut tokens = line.text.split_ascii_whitespace();
+            let first = tokens.next();
+
+            if let Some(directive) = first.and_then(|token| token.strip_prefix('%')) {
+                match directive {
+                    "include" => self.include(line, out, depth)?,
+                    "macro" => {
+                        let end = find_end(lines, index, "macro", "endmacro")?;
+                        self.define(line, &lines[index + 1..end])?;
+                        index = end;
+                    }
+                    "repeat" => {
+                        let end = find_end(lines, index, "repeat", "endrepeat")?;
+                        let count = parse_repeat_count(line)?;
+                        let body = reparent(&lines[index + 1..end], &line.origin);
+                        for _ in 0..count {
+                            self.count_line(line)?;
+                            self.expand_nested(line, &body, out, depth)?;
+                        }
+                        index = end;
+                    }
+                    "endmacro" | "endrepeat" => {
+                        return Err(error(
+                            &line.origin,
+                            ErrorKind::UnexpectedDirective(directive.to_string()),
+                        ))
+                    }
+                    _ => {
+                        return Err(error(
+                            &line.origin,
+                            ErrorKind::UnknownDirective(directive.to_string()),
+                        ))
+                    }
+                }
+            } else if let Some(mac) = first.and_then(|name| self.macros.get(name)) {
+                let name = first.unwrap();
+                let args: Vec<&str> = tokens.collect();
+                if args.len() != mac.params.len() {
+                    return Err(error(
+                        &line.origin,
+                        ErrorKind::ArgumentCount {
+                            name: name.to_string(),
+                            expected: mac.params.len(),
+                            found: args.len(),
+                        },
+                    ));
+                }
+
+                let bindings: HashMap<&str, &str> =
+                    mac.params.iter().map(String::as_str).zip(args).collect();
+                let body: Vec<Line> = reparent(&mac.body, &line.origin)
+                    .into_iter()
+                    .map(|body_line| Line {
+                        text: substitute(&body_line.text, &bindings),
+                        origin: body_line.origin,
+                    })
+                    .collect();
+
+                self.expand_nested(line, &body, out, depth)?;
+            } else {
+                out.push(line.clone());
+            }
+
+            index += 1;
+        }
+
+        Ok(())
+    }
+
+    fn count_line(&mut self, line: &Line) -> Result<(), PreprocessError> {
+        self.expanded += 1;
+        if self.expanded > MAX_EXPANDED_LINES {
+            return Err(error(&line.origin, ErrorKind::TooLarge));
+        }
+        Ok(())
+    }
+
+    fn expand_nested(
+        &mut self,
+        line: &Line,
+        body: &[Line],
+        out: &mut Vec<Line>,
+        depth: usize,
+    ) -> Result<(), PreprocessError> {
+        if depth >= MAX_EXPANSION_DEPTH {
+            return Err(error(&line.origin, ErrorKind::TooDeep));
+        }
+
+        self.expand(body, out, depth + 1)
+    }
+
+    fn include(
+        &mut self,
+        line: &Line,
+        out: &mut Vec<Line>,
+        depth: usize,
+    ) -> Result<(), PreprocessError> {
+        let rest = line.text.trim()["%include".len()..].trim();
+        let name = rest
+            .strip_prefix('"')
+            .and_then(|rest| rest.strip_suffix('"'))
+            .filter(|name| !name.is_empty())
+            .ok_or_else(|| {
+                error(
+                    &line.origin,
+                    ErrorKind::MalformedDirective("expected %include \"file\"".to_string()),
+                )
+            })?;
+
+        let path = normalise(&match line.origin.file.parent() {
+            Some(dir) => dir.join(name),
+            None => PathBuf::from(name),
+        });
+
+        if self.include_stack.contains(&path) {
+            return Err(error(&line.origin, ErrorKind::IncludeCycle(path)));
+        }
+
+        let source = (self.loader)(&path).map_err(|err| error(&line.origin, ErrorKind::Io(err)))?;
+        let lines = to_lines(&path, &source);
+
+        self.include_stack.push(path);
+        let result = self.expand_nested(line, &lines, out, depth);
+        self.include_stack.pop();
+        result
+    }
+
+    fn define(&mut self, line: &Line, body: &[Line]) -> Result<(), PreprocessError> {
+        let mut tokens = line.text.split_ascii_whitespace().skip(1);
+        let name = tokens.next().ok_or_else(|| {
+            error(
+                &line.origin,
+                ErrorKind::MalformedDirective("expected %macro name [params...]".to_string()),
+            )
+        })?;
+
+        if RESERVED_NAMES.contains(&name.to_lowercase().as_str()) || name.starts_with('%') {
+            return Err(error(
+                &line.origin,
+                ErrorKind::ReservedName(name.to_string()),
+            ));
+        }
+        let params: Vec<String> = tokens.map(str::to_string).collect();
+
+        for body_line in body {
+            let references = body_line
+                .text
+                .split_ascii_whitespace()
+                .enumerate()
+                .filter_map(|(position, token)| {
+                    let reference = token.strip_prefix('%')?;
+                    // A directive is only a directive where an opcode would go.
+                    if position == 0 && DIRECTIVES.contains(&reference) {
+                        None
+                    } else {
+                        Some(reference)
+                    }
+                });
+            for reference in references {
+                if !params.iter().any(|param| param == reference) {
+                    return Err(error(
+                        &body_line.origin,
+                        ErrorKind::UndefinedParameter(reference.to_string()),
+                    ));
+                }
+            }
+        }
+
+        // A definition inside a `%repeat` body or a macro is seen once per expansion, so defining
+        // the same macro again is fine; only a different definition under the same name is not.
+        if let Some(existing) = self.macros.get(name) {
+            let same_body = existing
+                .body
+                .iter()
+                .map(|line| &line.text)
+                .eq(body.iter().map(|line| &line.text));
+            if existing.params == params && same_body {
+                return Ok(());
+            }
+            return Err(error(
+                &line.origin,
+                ErrorKind::DuplicateMacro(name.to_string()),
+            ));
+        }
+
+        self.macros.insert(
+            name.to_string(),
+            Macro {
+                params,
+                body: body.to_vec(),
+            },
+        );
+        Ok(())
+    }
+}
+
+/// `path` without `.` components and with each `..` cancelling the directory before it, so that
+/// `a.gj`, `./a.gj` and `lib/../a.gj` are recognised as the same include. Works on the path alone
+/// since the loader need not be the filesystem.
+fn normalise(path: &Path) -> PathBuf {
+    let mut normal = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir
+                if matches!(normal.components().next_back(), Some(Component::Normal(_))) =>
+            {
+                normal.pop();
+            }
+            _ => normal.push(component),
+        }
+    }
+    normal
+}
+
+fn to_lines(path: &Path, source: &str) -> Vec<Line> {
+    let file = Rc::new(path.to_path_buf());
+    source
+        .lines()
+        .enumerate()
+        .map(|(index, text)| Line {
+            text: text.to_string(),
+            origin: Origin {
+                file: file.clone(),
+                line: index + 1,
+                expanded_from: None,
+            },
+        })
+        .collect()
+}
+
+/// Marks every line of `body` as having been expanded from `parent`.
+fn reparent(body: &[Line], parent: &Origin) -> Vec<Line> {
+    let parent = Rc::new(parent.clone());
+    body.iter()
+        .map(|line| Line {
+            text: line.text.clone(),
+            origin: Origin {
+                expanded_from: Some(parent.clone()),
+                ..line.origin.clone()
+            },
+        })
+        .collect()
+}
+
+fn substitute(text: &str, bindings: &HashMap<&str, &str>) -> String {
+    text.split_ascii_whitespace()
+        .map(|token| {
+            token
+                .strip_prefix('%')
+                .and_then(|name| bindings.get(name))
+                .copied()
+                .unwrap_or(token)
+        })
+        .collect::<Vec<&str>>()
+        .join(" ")
+}
+
+/// Finds the line closing the block opened at `start`, allowing blocks of the same kind to nest.
+fn find_end(
+    lines: &[Line],
+    start: usize,
+    open: &'static str,
+    close: &'static str,
+) -> Result<usize, PreprocessError> {
+    let mut nesting = 0;
+
+    for (index, line) in lines.iter().enumerate().skip(start + 1) {
+        let directive = line
+            .text
+            .split_ascii_whitespace()
+            .next()
+            .and_then(|token| token.strip_prefix('%'));
+
+        match directive {
+            Some(name) if name == open => nesting += 1,
+            Some(name) if name == close && nesting == 0 => return Ok(index),
+            Some(name) if name == close => nesting -= 1,
+            _ => {}
+        }
+    }
+
+    Err(error(&lines[start].origin, ErrorKind::Unterminated(open)))
+}
+
+fn parse_repeat_count(line: &Line) -> Result<usize, PreprocessError> {
+    let tokens: Vec<&str> = line.text.split_ascii_whitespace().skip(1).collect();
+    match tokens.as_slice() {
+        [count] => match count.parse() {
+            Ok(count) if count <= MAX_EXPANDED_LINES => Ok(count),
+            Ok(_) => Err(error(&line.origin, ErrorKind::TooLarge)),
+            Err(_) => Err(error(
+                &line.origin,
+                ErrorKind::MalformedDirective(format!("invalid repeat count \"{}\"", count)),
+            )),
+        },
+        _ => Err(error(
+            &line.origin,
+            ErrorKind::MalformedDirective("expected %repeat count".to_string()),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn preprocessor(files: &[(&'static str, &'static str)]) -> Preprocessor {
+        let files: HashMap<PathBuf, &'static str> = files
+            .iter()
+            .map(|(name, source)| (PathBuf::from(name), *source))
+            .collect();
+        Preprocessor::with_loader(move |path| {
+            files
+                .get(path)
+                .map(|source| source.to_string())
+                .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "not found"))
+        })
+    }
+
+    #[test]
+    fn expands_macros_repeats_and_includes() {
+        let mut pre = preprocessor(&[
+            (
+                "main.gj",
+                "%include \"lib.gj\"\n%repeat 2\nbump +3\n%endrepeat\njmp +1",
+            ),
+            (
+                "lib.gj",
+                "%macro bump amount\nacc %amount\nnop +0\n%endmacro",
+            ),
+        ]);
+
+        let expanded = pre.preprocess_file("main.gj").unwrap();
+        assert_eq!(expanded.text(), "acc +3\nnop +0\nacc +3\nnop +0\njmp +1");
+        assert_eq!(
+            expanded.parse().unwrap(),
+            vec![
+                OpCode::Acc(3),
+                OpCode::Nop(0),
+                OpCode::Acc(3),
+                OpCode::Nop(0),
+                OpCode::Jmp(1)
+            ]
+        );
+
+        let origin = &expanded.lines()[0].origin;
+        assert_eq!(
+            origin.to_string(),
+            "lib.gj:2 (expanded from main.gj:3 (expanded from main.gj:2))"
+        );
+    }
+
+    #[test]
+    fn reports_errors_against_original_lines() {
+        let mut pre = preprocessor(&[(
+            "main.gj",
+            "%macro twice op\n%op +1\n%op +1\n%endmacro\nnop +0\ntwice bogus",
+        )]);
+
+        let err = pre.preprocess_file("main.gj").unwrap().parse().unwrap_err();
+        let origin = err.origin.unwrap();
+        assert_eq!(origin.line, 2);
+        assert_eq!(origin.expanded_from.unwrap().line, 6);
+    }
+
+    #[test]
+    fn rejects_include_cycles() {
+        let mut pre = preprocessor(&[("a.gj", "%include \"b.gj\""), ("b.gj", "%include \"a.gj\"")]);
+
+        match pre.preprocess_file("a.gj") {
+            Err(PreprocessError {
+                kind: ErrorKind::IncludeCycle(_),
+                ..
+            }) => {}
+            _ => panic!("expected an include cycle"),
+        }
+
+        for include in &["./a.gj", "lib/../a.gj"] {
+            let source = format!("%include \"{}\"", include);
+            let mut pre = preprocessor(&[("a.gj", "acc +1")]);
+            assert_eq!(
+                pre.preprocess_str("main.gj", &source).unwrap().text(),
+                "acc +1"
+            );
+
+            let mut pre = preprocessor(&[]);
+            let err = pre.preprocess_str("a.gj", &source).err().unwrap();
+            assert!(
+                matches!(err.kind, ErrorKind::IncludeCycle(_)),
+                "{}",
+                include
+            );
+        }
+    }
+
+    #[test]
+    fn allows_identical_definitions_in_repeated_blocks() {
+        let mut pre = preprocessor(&[(
+            "main.gj",
+            "%repeat 2\n%macro inc\nacc +1\n%endmacro\ninc\n%endrepeat\n\
+             %macro twice op\n%macro step\n%op +1\n%endmacro\nstep\nstep\n%endmacro\n\
+             twice jmp\ntwice jmp",
+        )]);
+        assert_eq!(
+            pre.preprocess_file("main.gj").unwrap().text(),
+            "acc +1\nacc +1\njmp +1\njmp +1\njmp +1\njmp +1"
+        );
+
+        let mut pre = preprocessor(&[(
+            "main.gj",
+            "%macro twice op\n%macro step\n%op +1\n%endmacro\nstep\n%endmacro\n\
+             twice jmp\ntwice acc",
+        )]);
+        let err = pre.preprocess_file("main.gj").err().unwrap();
+        assert!(matches!(err.kind, ErrorKind::DuplicateMacro(name) if name == "step"));
+    }
+
+    #[test]
+    fn checks_parameters_in_opcode_position() {
+        let mut pre = preprocessor(&[(
+            "main.gj",
+            "%macro twice op\n%repeat 2\n%op +1\n%endrepeat\n%endmacro\ntwice acc",
+        )]);
+        assert_eq!(
+            pre.preprocess_file("main.gj").unwrap().text(),
+            "acc +1\nacc +1"
+        );
+
+        let mut pre = preprocessor(&[("main.gj", "%macro twice op\n%OP +1\n%endmacro")]);
+        let err = pre.preprocess_file("main.gj").err().unwrap();
+        assert_eq!(err.origin.unwrap().line, 2);
+        assert!(matches!(err.kind, ErrorKind::UndefinedParameter(name) if name == "OP"));
+    }
+
+    #[test]
+    fn limits_the_size_of_expansions() {
+        let too_large = |source: &'static str| {
+            let mut pre = preprocessor(&[("main.gj", source)]);
+            matches!(
+                pre.preprocess_file("main.gj"),
+                Err(PreprocessError {
+                    kind: ErrorKind::TooLarge,
+                    ..
+                })
+            )
+        };
+
+        assert!(too_large("%repeat 4000000000\nnop +0\n%endrepeat"));
+        assert!(too_large(
+            "%repeat 1000\n%repeat 1000\n%repeat 1000\n%endrepeat\n%endrepeat\n%endrepeat"
+        ));
+        assert!(too_large(
+            "%repeat 1000\n%repeat 1000\nnop +0\n%endrepeat\n%endrepeat"
+        ));
+        assert!(!too_large("%repeat 1000\nnop +0\n%endrepeat"));
+    }
+}
@@ -0,0 +1,399 @@
+use std::error::Error;
+use std::fmt;
+
+/// Machine state visible to watch expressions.
+pub struct State<'a> {
+    pub accumulator: i32,
+    pub instruction_pointer: usize,
+    pub steps: usize,
+    /// How many times each instruction has been executed, indexed by instruction pointer.
+    pub hits: &'a [usize],
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Var {
+    Acc,
+    Ip,
+    Steps,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UnaryOp {
+    Not,
+    Neg,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BinaryOp {
+    Or,
+    And,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+}
+
+/// An expression over machine state such as `acc > 100 && ip == 42` or `hits(ip=7) == 3`.
+///
+/// Values are integers; comparisons and logical operators produce `1` or `0` and any non-zero
+/// value counts as true.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Number(i64),
+    Var(Var),
+    Hits(Box<Expr>),
+    Unary(UnaryOp, Box<Expr>),
+    Binary(BinaryOp, Box<Expr>, Box<Expr>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum EvalError {
+    DivisionByZero,
+    Overflow,
+}
+
+impl fmt::Display for EvalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EvalError::DivisionByZero => write!(f, "division by zero"),
+            EvalError::Overflow => write!(f, "arithmetic overflow"),
+        }
+    }
+}
+
+impl Error for EvalError {}
+
+impl Expr {
+    pub fn eval(&self, state: &State) -> Result<i64, EvalError> {
+        match self {
+            Expr::Number(n) => Ok(*n),
+            Expr::Var(Var::Acc) => Ok(state.accumulator as i64),
+            Expr::Var(Var::Ip) => Ok(state.instruction_pointer as i64),
+            Expr::Var(Var::Steps) => Ok(state.steps as i64),
+            Expr::Hits(ip) => {
+                let ip = ip.eval(state)?;
+                let hits = if ip < 0 {
+                    0
+                } else {
+                    state.hits.get(ip as usize).copied().unwrap_or(0)
+                };
+                Ok(hits as i64)
+            }
+            Expr::Unary(UnaryOp::Not, inner) => Ok((inner.eval(state)? == 0) as i64),
+            Expr::Unary(UnaryOp::Neg, inner) => {
+                inner.eval(state)?.checked_neg().ok_or(EvalError::Overflow)
+            }
+            Expr::Binary(BinaryOp::Or, lhs, rhs) => {
+                Ok((lhs.eval(state)? != 0 || rhs.eval(state)? != 0) as i64)
+            }
+            Expr::Binary(BinaryOp::And, lhs, rhs) => {
+                Ok((lhs.eval(state)? != 0 && rhs.eval(state)? != 0) as i64)
+            }
+            Expr::Binary(op, lhs, rhs) => {
+                let lhs = lhs.eval(state)?;
+                let rhs = rhs.eval(state)?;
+                match op {
+                    BinaryOp::Eq => Ok((lhs == rhs) as i64),
+                    BinaryOp::Ne => Ok((lhs != rhs) as i64),
+                    BinaryOp::Lt => Ok((lhs < rhs) as i64),
+                    BinaryOp::Le => Ok((lhs <= rhs) as i64),
+                    BinaryOp::Gt => Ok((lhs > rhs) as i64),
+                    BinaryOp::Ge => Ok((lhs >= rhs) as i64),
+                    BinaryOp::Add => lhs.checked_add(rhs).ok_or(EvalError::Overflow),
+                    BinaryOp::Sub => lhs.checked_sub(rhs).ok_or(EvalError::Overflow),
+                    BinaryOp::Mul => lhs.checked_mul(rhs).ok_or(EvalError::Overflow),
+                    BinaryOp::Div | BinaryOp::Rem if rhs == 0 => Err(EvalError::DivisionByZero),
+                    BinaryOp::Div => lhs.checked_div(rhs).ok_or(EvalError::Overflow),
+                    BinaryOp::Rem => lhs.checked_rem(rhs).ok_or(EvalError::Overflow),
+                    BinaryOp::Or | BinaryOp::And => unreachable!(),
+                }
+            }
+        }
+    }
+
+    pub fn is_true(&self, state: &State) -> Result<bool, EvalError> {
+        Ok(self.eval(state)? != 0)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ParseError {
+    /// Byte offset into the expression where parsing failed.
+    pub position: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "at column {}: {}", self.position + 1, self.message)
+    }
+}
+
+impl Error for ParseError {}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(i64),
+    Ident(String),
+    Op(&'static str),
+    Open,
+    Close,
+}
+
+const OPERATORS: [&str; 15] = [
+    "||", "&&", "==", "!=", "<=", ">=", "<", ">", "+", "-", "*", "/", "%", "!", "=",
+];
+
+fn tokenize(source: &str) -> Result<Vec<(usize, Token)>, ParseError> {
+    let mut tokens = vec![];
+    let mut position = 0;
+
+    while position < source.len() {
+        let rest = &source[position..];
+        let c = rest.chars().next().unwrap();
+
+        if c.is_whitespace() {
+            position += c.len_utf8();
+        } else if c.is_ascii_digit() {
+            let len = rest
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(rest.len());
+            let value = rest[..len].parse().map_err(|_| ParseError {
+                position,
+                message: "number is too large".to_string(),
+            })?;
+            tokens.push((position, Token::Number(value)));
+            position += len;
+        } else if c.is_ascii_alphabetic() || c == '_' {
+            let len = rest
+                .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+                .unwrap_or(rest.len());
+            tokens.push((position, Token::Ident(rest[..len].to_lowercase())));
+            position += len;
+        } else if c == '(' || c == ')' {
+            tokens.push((position, if c == '(' { Token::Open } else { Token::Close }));
+            position += 1;
+        } else if let Some(op) = OPERATORS.iter().find(|op| rest.starts_with(*op)) {
+            tokens.push((position, Token::Op(op)));
+            position += op.len();
+        } else {
+            return Err(ParseError {
+                position,
+                message: format!("unexpected character '{}'", c),
+            });
+        }
+    }
+
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    index: usize,
+    end: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.index).map(|(_, token)| token)
+    }
+
+    fn position(&self) -> usize {
+        self.tokens
+            .get(self.index)
+            .map(|(position, _)| *position)
+            .unwrap_or(self.end)
+    }
+
+    fn error<T>(&self, message: &str) -> Result<T, ParseError> {
+        Err(ParseError {
+            position: self.position(),
+            message: message.to_string(),
+        })
+    }
+
+    fn eat_op(&mut self, op: &str) -> bool {
+        if matches!(self.peek(), Some(Token::Op(symbol)) if *symbol == op) {
+            self.index += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, token: Token, message: &str) -> Result<(), ParseError> {
+        if self.peek() == Some(&token) {
+            self.index += 1;
+            Ok(())
+        } else {
+            self.error(message)
+        }
+    }
+
+    /// Parses one precedence level of left-associative binary operators.
+    fn binary(
+        &mut self,
+        ops: &[(&str, BinaryOp)],
+        next: fn(&mut Parser) -> Result<Expr, ParseError>,
+    ) -> Result<Expr, ParseError> {
+        let mut lhs = next(self)?;
+
+        'outer: loop {
+            for (symbol, op) in ops {
+                if self.eat_op(symbol) {
+                    let rhs = next(self)?;
+                    lhs = Expr::Binary(*op, Box::new(lhs), Box::new(rhs));
+                    continue 'outer;
+                }
+            }
+            return Ok(lhs);
+        }
+    }
+
+    fn or(&mut self) -> Result<Expr, ParseError> {
+        self.binary(&[("||", BinaryOp::Or)], Parser::and)
+    }
+
+    fn and(&mut self) -> Result<Expr, ParseError> {
+        self.binary(&[("&&", BinaryOp::And)], Parser::comparison)
+    }
+
+    fn comparison(&mut self) -> Result<Expr, ParseError> {
+        self.binary(
+            &[
+                ("==", BinaryOp::Eq),
+                ("!=", BinaryOp::Ne),
+                ("<=", BinaryOp::Le),
+                (">=", BinaryOp::Ge),
+                ("<", BinaryOp::Lt),
+                (">", BinaryOp::Gt),
+            ],
+            Parser::sum,
+        )
+    }
+
+    fn sum(&mut self) -> Result<Expr, ParseError> {
+        self.binary(
+            &[("+", BinaryOp::Add), ("-", BinaryOp::Sub)],
+            Parser::product,
+        )
+    }
+
+    fn product(&mut self) -> Result<Expr, ParseError> {
+        self.binary(
+            &[
+                ("*", BinaryOp::Mul),
+                ("/", BinaryOp::Div),
+                ("%", BinaryOp::Rem),
+            ],
+            Parser::unary,
+        )
+    }
+
+    fn unary(&mut self) -> Result<Expr, ParseError> {
+        if self.eat_op("!") {
+            Ok(Expr::Unary(UnaryOp::Not, Box::new(self.unary()?)))
+        } else if self.eat_op("-") {
+            Ok(Expr::Unary(UnaryOp::Neg, Box::new(self.unary()?)))
+        } else {
+            self.primary()
+        }
+    }
+
+    fn primary(&mut self) -> Result<Expr, ParseError> {
+        let token = match self.peek() {
+            Some(token) => token.clone(),
+            None => return self.error("unexpected end of expression"),
+        };
+        self.index += 1;
+
+        match token {
+            Token::Number(n) => Ok(Expr::Number(n)),
+            Token::Open => {
+                let inner = self.or()?;
+                self.expect(Token::Close, "expected ')'")?;
+                Ok(inner)
+            }
+            Token::Ident(name) => match name.as_str() {
+                "acc" => Ok(Expr::Var(Var::Acc)),
+                "ip" => Ok(Expr::Var(Var::Ip)),
+                "steps" => Ok(Expr::Var(Var::Steps)),
+                "hits" => self.hits(),
+                _ => {
+                    self.index -= 1;
+                    self.error(&format!("unknown name \"{}\"", name))
+                }
+            },
+            _ => {
+                self.index -= 1;
+                self.error("expected a number, name or '('")
+            }
+        }
+    }
+
+    /// `hits(ip=7)`, or the shorthand `hits(7)`.
+    fn hits(&mut self) -> Result<Expr, ParseError> {
+        self.expect(Token::Open, "expected '(' after hits")?;
+        if self.peek() == Some(&Token::Ident("ip".to_string()))
+            && self.tokens.get(self.index + 1).map(|(_, token)| token) == Some(&Token::Op("="))
+        {
+            self.index += 2;
+        }
+        let ip = self.or()?;
+        self.expect(Token::Close, "expected ')'")?;
+        Ok(Expr::Hits(Box::new(ip)))
+    }
+}
+
+pub fn parse(source: &str) -> Result<Expr, ParseError> {
+    let mut parser = Parser {
+        tokens: tokenize(source)?,
+        index: 0,
+        end: source.len(),
+    };
+
+    let expr = parser.or()?;
+    if parser.peek().is_some() {
+        return parser.error("unexpected trailing input");
+    }
+    Ok(expr)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn eval(source: &str, accumulator: i32, instruction_pointer: usize) -> i64 {
+        let hits = [0, 0, 0, 0, 0, 0, 0, 3];
+        let state = State {
+            accumulator,
+            instruction_pointer,
+            steps: 10,
+            hits: &hits,
+        };
+        parse(source).unwrap().eval(&state).unwrap()
+    }
+
+    #[test]
+    fn evaluates_over_machine_state() {
+        assert_eq!(eval("acc > 100 && ip == 42", 101, 42), 1);
+        assert_eq!(eval("acc > 100 && ip == 42", 100, 42), 0);
+        assert_eq!(eval("hits(ip=7) == 3", 0, 0), 1);
+        assert_eq!(eval("hits(7) + steps * 2 - -acc", 5, 0), 28);
+        assert_eq!(eval("!(ip < 3) || acc % 2 != 0", 4, 1), 0);
+    }
+
+    #[test]
+    fn reports_parse_errors_with_position() {
+        assert_eq!(parse("acc > ").unwrap_err().position, 6);
+        assert_eq!(parse("ac > 1").unwrap_err().position, 0);
+        assert_eq!(parse("hits(ip=7").unwrap_err().position, 9);
+    }
+}
@@ -0,0 +1,403 @@
+use std::error::Error;
+use std::fmt;
+use std::ops::{Index, IndexMut};
+
+/// The four orthogonal steps, as `(dx, dy)` with `y` growing downwards.
+pub const DIRECTIONS4: [(isize, isize); 4] = [(0, -1), (-1, 0), (1, 0), (0, 1)];
+/// The orthogonal and diagonal steps, as `(dx, dy)` with `y` growing downwards.
+pub const DIRECTIONS8: [(isize, isize); 8] = [
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (-1, 0),
+    (1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+];
+
+/// A cell position; `x` is the column and `y` the row, both counted from the top left.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Pos {
+    pub x: usize,
+    pub y: usize,
+}
+
+impl Pos {
+    pub fn new(x: usize, y: usize) -> Pos {
+        Pos { x, y }
+    }
+}
+
+/// What happens to positions that step off an edge of the grid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Wrap {
+    /// Positions outside the grid do not exist.
+    Clamped,
+    /// The grid repeats to the left and right, like the toboggan map of day 3.
+    Horizontal,
+    /// The grid repeats in every direction.
+    Toroidal,
+}
+
+/// A cell type that has a one character representation in puzzle inputs.
+pub trait Cell: Sized {
+    fn from_char(c: char) -> Option<Self>;
+    fn to_char(&self) -> char;
+}
+
+impl Cell for char {
+    fn from_char(c: char) -> Option<char> {
+        Some(c)
+    }
+
+    fn to_char(&self) -> char {
+        *self
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseError {
+    /// `line` and `column` are 1-based.
+    InvalidCell {
+        line: usize,
+        column: usize,
+        found: char,
+    },
+    /// A row whose length differs from the first row's.
+    Ragged {
+        line: usize,
+        expected: usize,
+        found: usize,
+    },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::InvalidCell {
+                line,
+                column,
+                found,
+            } => write!(f, "{}:{}: unexpected {:?}", line, column, found),
+            ParseError::Ragged {
+                line,
+                expected,
+                found,
+            } => write!(
+                f,
+                "{}: row is {} cells wide, expected {}",
+                line, found, expected
+            ),
+        }
+    }
+}
+
+impl Error for ParseError {}
+
+/// A rectangular grid of cells stored row by row.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+    wrap: Wrap,
+}
+
+impl<T: Cell> Grid<T> {
+    /// Reads a character map with one row per line.
+    pub fn parse(text: &str) -> Result<Grid<T>, ParseError> {
+        Grid::parse_with(text, T::from_char)
+    }
+
+    /// Renders the grid back into the text it was parsed from.
+    pub fn render(&self) -> String {
+        self.render_with(T::to_char)
+    }
+}
+
+impl<T> Grid<T> {
+    pub fn new(width: usize, height: usize, fill: T) -> Grid<T>
+    where
+        T: Clone,
+    {
+        Grid {
+            width,
+            height,
+            cells: vec![fill; width * height],
+            wrap: Wrap::Clamped,
+        }
+    }
+
+    /// Reads a character map with one row per line, converting each character with `cell`.
+    pub fn parse_with<F>(text: &str, cell: F) -> Result<Grid<T>, ParseError>
+    where
+        F: Fn(char) -> Option<T>,
+    {
+        let mut width = None;
+        let mut height = 0;
+        let mut cells = Vec::new();
+
+        for (row, line) in text.lines().enumerate() {
+            let mut found = 0;
+            for (column, c) in line.chars().enumerate() {
+                cells.push(cell(c).ok_or(ParseError::InvalidCell {
+                    line: row + 1,
+                    column: column + 1,
+                    found: c,
+                })?);
+                found += 1;
+            }
+
+            let expected = *width.get_or_insert(found);
+            if found != expected {
+                return Err(ParseError::Ragged {
+                    line: row + 1,
+                    expected,
+                    found,
+                });
+            }
+            height += 1;
+        }
+
+        Ok(Grid {
+            width: width.unwrap_or(0),
+            height,
+            cells,
+            wrap: Wrap::Clamped,
+        })
+    }
+
+    pub fn with_wrap(mut self, wrap: Wrap) -> Grid<T> {
+        self.wrap = wrap;
+        self
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn wrap(&self) -> Wrap {
+        self.wrap
+    }
+
+    pub fn get(&self, pos: Pos) -> Option<&T> {
+        if pos.x < self.width && pos.y < self.height {
+            self.cells.get(pos.y * self.width + pos.x)
+        } else {
+            None
+        }
+    }
+
+    pub fn get_mut(&mut self, pos: Pos) -> Option<&mut T> {
+        if pos.x < self.width && pos.y < self.height {
+            self.cells.get_mut(pos.y * self.width + pos.x)
+        } else {
+            None
+        }
+    }
+
+    /// The cells row by row.
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.cells.iter()
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        // `max(1)` keeps `chunks` happy for an empty grid, which has no cells to chunk anyway.
+        self.cells.chunks(self.width.max(1))
+    }
+
+    /// Every position row by row, in the same order as `iter`.
+    pub fn positions(&self) -> impl Iterator<Item = Pos> {
+        let width = self.width;
+        (0..self.height).flat_map(move |y| (0..width).map(move |x| Pos::new(x, y)))
+    }
+
+    /// The position one `(dx, dy)` step away from `pos`, following the wrap mode.
+    pub fn step(&self, pos: Pos, direction: (isize, isize)) -> Option<Pos> {
+        self.step_wrapping(pos, direction, self.wrap)
+    }
+
+    /// Like [`Grid::step`], but following `wrap` instead of the grid's own wrap mode.
+    pub fn step_wrapping(&self, pos: Pos, (dx, dy): (isize, isize), wrap: Wrap) -> Option<Pos> {
+        let x = pos.x as isize + dx;
+        let y = pos.y as isize + dy;
+        let (width, height) = (self.width as isize, self.height as isize);
+        if width == 0 || height == 0 {
+            return None;
+        }
+
+        let (x, y) = match wrap {
+            Wrap::Clamped => (x, y),
+            Wrap::Horizontal => (x.rem_euclid(width), y),
+            Wrap::Toroidal => (x.rem_euclid(width), y.rem_euclid(height)),
+        };
+
+        if (0..width).contains(&x) && (0..height).contains(&y) {
+            Some(Pos::new(x as usize, y as usize))
+        } else {
+            None
+        }
+    }
+
+    /// Positions stepped to from `pos` in each of `directions` that are on the grid.
+    pub fn neighbours<'a>(
+        &'a self,
+        pos: Pos,
+        directions: &'a [(isize, isize)],
+    ) -> impl Iterator<Item = Pos> + 'a {
+        directions
+            .iter()
+            .filter_map(move |direction| self.step(pos, *direction))
+    }
+
+    pub fn neighbours4(&self, pos: Pos) -> impl Iterator<Item = Pos> + '_ {
+        self.neighbours(pos, &DIRECTIONS4)
+    }
+
+    pub fn neighbours8(&self, pos: Pos) -> impl Iterator<Item = Pos> + '_ {
+        self.neighbours(pos, &DIRECTIONS8)
+    }
+
+    /// The positions reached by repeatedly stepping `direction` from `pos`, not including `pos`
+    /// itself. The ray ends when it leaves the grid or, on a wrapping grid, comes back to `pos`.
+    pub fn ray(&self, pos: Pos, direction: (isize, isize)) -> Ray<'_, T> {
+        self.ray_wrapping(pos, direction, self.wrap)
+    }
+
+    /// Like [`Grid::ray`], but following `wrap` instead of the grid's own wrap mode. A `pos` off
+    /// the grid is first wrapped like any other step, so the ray can still come back to it.
+    pub fn ray_wrapping(&self, pos: Pos, direction: (isize, isize), wrap: Wrap) -> Ray<'_, T> {
+        let start = self.step_wrapping(pos, (0, 0), wrap).unwrap_or(pos);
+        Ray {
+            grid: self,
+            start,
+            current: Some(start),
+            direction,
+            wrap,
+        }
+    }
+
+    /// Renders the grid as text, one line per row, converting each cell with `cell`.
+    pub fn render_with<F>(&self, cell: F) -> String
+    where
+        F: Fn(&T) -> char,
+    {
+        let mut text = String::with_capacity((self.width + 1) * self.height);
+        for row in self.rows() {
+            text.extend(row.iter().map(&cell));
+            text.push('\n');
+        }
+        text
+    }
+}
+
+impl<T> Index<Pos> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, pos: Pos) -> &T {
+        self.get(pos).expect("position outside the grid")
+    }
+}
+
+impl<T> IndexMut<Pos> for Grid<T> {
+    fn index_mut(&mut self, pos: Pos) -> &mut T {
+        self.get_mut(pos).expect("position outside the grid")
+    }
+}
+
+impl<T: Cell> fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.render())
+    }
+}
+
+/// Iterator returned by [`Grid::ray`].
+pub struct Ray<'a, T> {
+    grid: &'a Grid<T>,
+    start: Pos,
+    current: Option<Pos>,
+    direction: (isize, isize),
+    wrap: Wrap,
+}
+
+impl<'a, T> Iterator for Ray<'a, T> {
+    type Item = Pos;
+
+    fn next(&mut self) -> Option<Pos> {
+        let next = self
+            .grid
+            .step_wrapping(self.current?, self.direction, self.wrap)
+            .filter(|next| *next != self.start);
+        self.current = next;
+        next
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_renders_and_rejects_bad_maps() {
+        let grid: Grid<char> = Grid::parse("ab\ncd\n").unwrap();
+        assert_eq!((grid.width(), grid.height()), (2, 2));
+        assert_eq!(grid[Pos::new(1, 0)], 'b');
+        assert_eq!(grid.render(), "ab\ncd\n");
+
+        assert_eq!(
+            Grid::<char>::parse("ab\nc\n"),
+            Err(ParseError::Ragged {
+                line: 2,
+                expected: 2,
+                found: 1
+            })
+        );
+        assert_eq!(
+            Grid::parse_with("..\n.x", |c| Some(c).filter(|c| *c == '.')),
+            Err(ParseError::InvalidCell {
+                line: 2,
+                column: 2,
+                found: 'x'
+            })
+        );
+    }
+
+    #[test]
+    fn neighbours_and_rays_follow_wrap_mode() {
+        let grid = Grid::new(3, 2, 0);
+        let corner = Pos::new(0, 0);
+        assert_eq!(grid.neighbours4(corner).count(), 2);
+        assert_eq!(grid.neighbours8(corner).count(), 3);
+        assert_eq!(
+            grid.ray(corner, (1, 1)).collect::<Vec<_>>(),
+            [Pos::new(1, 1)]
+        );
+
+        let grid = grid.with_wrap(Wrap::Horizontal);
+        assert_eq!(grid.neighbours8(corner).count(), 5);
+        assert_eq!(
+            grid.ray(corner, (2, 1)).collect::<Vec<_>>(),
+            [Pos::new(2, 1)]
+        );
+        assert_eq!(
+            grid.ray(corner, (-1, 0)).collect::<Vec<_>>(),
+            [Pos::new(2, 0), Pos::new(1, 0)]
+        );
+
+        let grid = grid.with_wrap(Wrap::Toroidal);
+        assert_eq!(grid.neighbours4(corner).count(), 4);
+        assert_eq!(grid.ray(corner, (1, 1)).count(), 5);
+        // A start off the grid is wrapped onto it, so the ray still ends.
+        assert_eq!(grid.ray(Pos::new(30, 20), (1, 1)).count(), 5);
+        assert_eq!(
+            grid.ray_wrapping(Pos::new(4, 1), (1, 0), Wrap::Horizontal)
+                .collect::<Vec<_>>(),
+            [Pos::new(2, 1), Pos::new(0, 1)]
+        );
+    }
+}
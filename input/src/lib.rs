@@ -0,0 +1,150 @@
+use std::env;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io::{self, Read};
+use std::path::PathBuf;
+
+/// Environment variable naming an input file, used when no path is given on the command line.
+pub const INPUT_VAR: &str = "AOC_INPUT";
+
+/// Where a puzzle input comes from.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Source {
+    File(PathBuf),
+    Stdin,
+    /// A file whose path is held in the named environment variable.
+    Env(String),
+    /// The worked example bundled with a day.
+    Example(&'static str),
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Source::File(path) => write!(f, "{}", path.display()),
+            Source::Stdin => write!(f, "<stdin>"),
+            Source::Env(var) => write!(f, "${}", var),
+            Source::Example(_) => write!(f, "<example>"),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum InputError {
+    Io(Source, io::Error),
+    MissingVar(String),
+    /// The input is not valid UTF-8; `line` and `column` are 1-based, `column` counts bytes.
+    Encoding {
+        source: Source,
+        line: usize,
+        column: usize,
+    },
+}
+
+impl fmt::Display for InputError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InputError::Io(source, err) => write!(f, "{}: {}", source, err),
+            InputError::MissingVar(var) => write!(f, "${} is not set", var),
+            InputError::Encoding {
+                source,
+                line,
+                column,
+            } => write!(
+                f,
+                "{}:{}:{}: input is not valid UTF-8",
+                source, line, column
+            ),
+        }
+    }
+}
+
+impl Error for InputError {}
+
+impl Source {
+    /// Picks the source for a run: an explicit `path` (`-` meaning stdin) wins, then the bundled
+    /// example if asked for, then `$AOC_INPUT`, and finally `default`.
+    pub fn resolve(
+        path: Option<PathBuf>,
+        example: Option<&'static str>,
+        default: PathBuf,
+    ) -> Source {
+        match (path, example) {
+            (Some(path), _) if path.as_os_str() == "-" => Source::Stdin,
+            (Some(path), _) => Source::File(path),
+            (None, Some(example)) => Source::Example(example),
+            (None, None) if env::var_os(INPUT_VAR).is_some() => Source::Env(INPUT_VAR.to_string()),
+            (None, None) => Source::File(default),
+        }
+    }
+
+    /// Reads the whole input, rejecting invalid UTF-8 and normalising line endings.
+    pub fn read(&self) -> Result<String, InputError> {
+        let bytes = match self {
+            Source::File(path) => {
+                fs::read(path).map_err(|err| InputError::Io(self.clone(), err))?
+            }
+            Source::Stdin => {
+                let mut bytes = vec![];
+                io::stdin()
+                    .read_to_end(&mut bytes)
+                    .map_err(|err| InputError::Io(self.clone(), err))?;
+                bytes
+            }
+            Source::Env(var) => {
+                let path = env::var_os(var).ok_or_else(|| InputError::MissingVar(var.clone()))?;
+                fs::read(&path)
+                    .map_err(|err| InputError::Io(Source::File(PathBuf::from(path.clone())), err))?
+            }
+            Source::Example(example) => example.as_bytes().to_vec(),
+        };
+
+        decode(self, bytes)
+    }
+}
+
+fn decode(source: &Source, bytes: Vec<u8>) -> Result<String, InputError> {
+    match String::from_utf8(bytes) {
+        Ok(text) => Ok(normalise(&text)),
+        Err(err) => {
+            let valid = &err.as_bytes()[..err.utf8_error().valid_up_to()];
+            let line_start = valid
+                .iter()
+                .rposition(|b| *b == b'\n')
+                .map(|pos| pos + 1)
+                .unwrap_or(0);
+            Err(InputError::Encoding {
+                source: source.clone(),
+                line: valid.iter().filter(|b| **b == b'\n').count() + 1,
+                column: valid.len() - line_start + 1,
+            })
+        }
+    }
+}
+
+/// Strips a byte order mark and turns `\r\n` and lone `\r` line endings into `\n`.
+pub fn normalise(text: &str) -> String {
+    text.trim_start_matches('\u{feff}')
+        .replace("\r\n", "\n")
+        .replace('\r', "\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalises_line_endings() {
+        assert_eq!(normalise("\u{feff}a\r\nb\rc\n"), "a\nb\nc\n");
+    }
+
+    #[test]
+    fn reports_position_of_invalid_utf8() {
+        let source = Source::Example("");
+        match decode(&source, b"ok\nab\xffc".to_vec()) {
+            Err(InputError::Encoding { line, column, .. }) => assert_eq!((line, column), (2, 3)),
+            other => panic!("expected an encoding error, got {:?}", other),
+        }
+    }
+}